@@ -3,5 +3,5 @@ pub mod symbol_table;
 pub mod ternary_trie;
 
 pub use patricia_trie::PatriciaTrie;
-pub use symbol_table::{PrefixSearch, SymbolTable};
+pub use symbol_table::{FuzzySearch, PrefixSearch, SymbolTable};
 pub use ternary_trie::TernarySearchTrie;
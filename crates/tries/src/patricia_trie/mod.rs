@@ -21,9 +21,10 @@
 //! assert_eq!(trie.longest_prefix_of("applying"), Some("apply".to_string()));
 //! ```
 
-use crate::symbol_table::{PrefixSearch, SymbolTable};
+use crate::symbol_table::{FuzzySearch, PrefixSearch, SymbolTable, next_distance_row};
 use std::collections::BTreeMap;
 
+#[allow(clippy::module_inception)]
 mod tests;
 
 struct Node<E> {
@@ -65,6 +66,27 @@ impl<E> PatriciaTrie<E> {
             size: 0,
         }
     }
+
+    /// Number of nodes below the root.
+    pub fn node_count(&self) -> usize {
+        Self::count_nodes(&self.root) - 1
+    }
+
+    /// Approximate bytes held by the nodes and their labels, not counting
+    /// allocator or map bookkeeping overhead.
+    pub fn memory_usage(&self) -> usize {
+        Self::node_memory(&self.root)
+    }
+
+    fn count_nodes(node: &Node<E>) -> usize {
+        1 + node.children.values().map(Self::count_nodes).sum::<usize>()
+    }
+
+    fn node_memory(node: &Node<E>) -> usize {
+        std::mem::size_of::<Node<E>>()
+            + node.label.capacity() * std::mem::size_of::<char>()
+            + node.children.values().map(Self::node_memory).sum::<usize>()
+    }
 }
 
 // Helpers that never touch values, so they need no `Clone` bound.
@@ -119,6 +141,27 @@ impl<E> PatriciaTrie<E> {
         }
     }
 
+    // Like `collect`, handing each key to `f` with its value instead; returns
+    // `false` once `f` has asked to stop
+    fn visit(node: &Node<E>, prefix: &mut String, f: &mut dyn FnMut(&str, &E) -> bool) -> bool {
+        if let Some(value) = &node.value
+            && !f(prefix, value)
+        {
+            return false;
+        }
+
+        for child in node.children.values() {
+            let restore_to = prefix.len();
+            prefix.extend(child.label.iter());
+            let keep_going = Self::visit(child, prefix, f);
+            prefix.truncate(restore_to);
+            if !keep_going {
+                return false;
+            }
+        }
+        true
+    }
+
     // Descends until `rest` is exhausted, then collects the subtree reached.
     // `rest` running out mid-edge is fine: every key below that edge still
     // starts with it.
@@ -244,6 +287,10 @@ impl<E: Clone> SymbolTable<E> for PatriciaTrie<E> {
         Self::collect(&self.root, &mut String::new(), &mut result);
         result
     }
+
+    fn for_each_entry(&self, f: &mut dyn FnMut(&str, &E) -> bool) {
+        Self::visit(&self.root, &mut String::new(), f);
+    }
 }
 
 impl<E: Clone> PatriciaTrie<E> {
@@ -321,3 +368,55 @@ impl<E> PrefixSearch for PatriciaTrie<E> {
         }
     }
 }
+
+impl<E> FuzzySearch for PatriciaTrie<E> {
+    fn get_keys_within_distance(&self, key: &str, max_distance: usize) -> Vec<(String, usize)> {
+        let chars: Vec<char> = key.chars().collect();
+        let first_row: Vec<usize> = (0..=chars.len()).collect();
+        let mut result = Vec::new();
+        Self::collect_within_distance(
+            &self.root,
+            &chars,
+            &first_row,
+            max_distance,
+            &mut String::new(),
+            &mut result,
+        );
+        result
+    }
+}
+
+impl<E> PatriciaTrie<E> {
+    // Extends the Levenshtein row one character at a time along each child's
+    // label, abandoning the edge as soon as no extension could come within
+    // `max_distance`. A node is only reported once its whole label is matched.
+    fn collect_within_distance(
+        node: &Node<E>,
+        key: &[char],
+        row: &[usize],
+        max_distance: usize,
+        prefix: &mut String,
+        result: &mut Vec<(String, usize)>,
+    ) {
+        if node.value.is_some() && row[key.len()] <= max_distance {
+            result.push((prefix.clone(), row[key.len()]));
+        }
+
+        'children: for child in node.children.values() {
+            let restore_to = prefix.len();
+            let mut child_row = row.to_vec();
+
+            for &c in &child.label {
+                child_row = next_distance_row(&child_row, c, key);
+                prefix.push(c);
+                if child_row.iter().min().is_none_or(|&min| min > max_distance) {
+                    prefix.truncate(restore_to);
+                    continue 'children;
+                }
+            }
+
+            Self::collect_within_distance(child, key, &child_row, max_distance, prefix, result);
+            prefix.truncate(restore_to);
+        }
+    }
+}
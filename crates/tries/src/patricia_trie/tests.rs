@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use crate::{PatriciaTrie, PrefixSearch, SymbolTable, TernarySearchTrie};
+    use crate::{FuzzySearch, PatriciaTrie, PrefixSearch, SymbolTable, TernarySearchTrie};
 
     fn build_trie() -> PatriciaTrie<i32> {
         let mut trie = PatriciaTrie::new();
@@ -183,11 +183,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_fuzzy_search() {
+        let trie = build_trie();
+
+        assert_eq!(
+            trie.get_keys_within_distance("shell", 1),
+            [("shells".to_string(), 1)]
+        );
+        assert_eq!(
+            trie.get_keys_within_distance("shell", 2),
+            [
+                ("sells".to_string(), 2),
+                ("she".to_string(), 2),
+                ("shells".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            trie.get_keys_within_distance("sea", 0),
+            [("sea".to_string(), 0)]
+        );
+        assert_eq!(trie.get_keys_within_distance("", 1), [("a".to_string(), 1)]);
+
+        // A mismatch partway along an edge prunes it without reporting the
+        // node below
+        let mut trie = PatriciaTrie::<i32>::new();
+        trie.put("application".to_string(), 1);
+        trie.put("apply".to_string(), 2);
+        assert_eq!(
+            trie.get_keys_within_distance("applicator", 2),
+            [("application".to_string(), 2)]
+        );
+        assert_eq!(trie.get_keys_within_distance("zzzzz", 2), []);
+    }
+
     // Everything observable about a table, so two implementations can be
     // compared directly rather than by re-asserting the same expectations twice
     fn snapshot<T>(words: &[&str], queries: &[&str], to_delete: &[&str]) -> Vec<String>
     where
-        T: SymbolTable<i32> + PrefixSearch + Default,
+        T: SymbolTable<i32> + PrefixSearch + FuzzySearch + Default,
     {
         let mut table = T::default();
         let mut log = Vec::new();
@@ -204,11 +238,12 @@ mod tests {
 
         for query in queries {
             log.push(format!(
-                "{query}: get={:?} contains={} lpo={:?} prefix={:?}",
+                "{query}: get={:?} contains={} lpo={:?} prefix={:?} fuzzy={:?}",
                 table.get(query),
                 table.contains(query),
                 table.longest_prefix_of(query),
-                table.get_keys_with_prefix(query)
+                table.get_keys_with_prefix(query),
+                table.get_keys_within_distance(query, 2)
             ));
         }
 
@@ -241,12 +276,9 @@ mod tests {
         log
     }
 
-    // ASCII only, deliberately: TernarySearchTrie::longest_prefix_of counts
-    // matched characters but then slices the query by byte offset, so on
-    // multi-byte input it either truncates ("héllo" -> "héll") or panics when
-    // the offset lands inside a character. PatriciaTrie rebuilds from chars and
-    // handles both - see test_multibyte_keys. Widening this test's inputs is
-    // the way to catch the regression once that is fixed.
+    // Includes multi-byte keys: TernarySearchTrie::longest_prefix_of used to
+    // slice the query by a character count as if it were a byte offset,
+    // truncating "héllo" to "héll" or panicking inside a character.
     #[test]
     fn test_matches_ternary_search_trie() {
         let words = [
@@ -264,6 +296,9 @@ mod tests {
             "apply",
             "sea",
             "zebra",
+            "héllo",
+            "héllo wörld",
+            "日本語",
         ];
         let queries = [
             "",
@@ -281,6 +316,9 @@ mod tests {
             "z",
             "zzz",
             "nomatch",
+            "hé",
+            "héllo there",
+            "日本語版",
         ];
         let to_delete = ["app", "sea", "zebra", "absent", "appl"];
 
@@ -290,4 +328,57 @@ mod tests {
             "PatriciaTrie and TernarySearchTrie disagree through the traits"
         );
     }
+
+    // Checks that `for_each_entry` visits what `get_all_keys` lists, in the
+    // same order and with the stored values, and stops when asked to
+    fn check_for_each_entry<T: SymbolTable<u32>>(trie: &T) {
+        let mut entries = Vec::new();
+        trie.for_each_entry(&mut |key, value| {
+            entries.push((key.to_string(), *value));
+            true
+        });
+        let keys: Vec<String> = entries.iter().map(|(key, _)| key.clone()).collect();
+        assert_eq!(keys, trie.get_all_keys());
+        assert!(
+            entries
+                .iter()
+                .all(|(key, value)| trie.get(key) == Some(*value))
+        );
+
+        let mut visited = 0;
+        trie.for_each_entry(&mut |_, _| {
+            visited += 1;
+            visited < 3
+        });
+        assert_eq!(visited, 3);
+    }
+
+    #[test]
+    fn test_node_count_and_for_each_entry() {
+        let mut ternary = TernarySearchTrie::<u32>::new();
+        let mut patricia = PatriciaTrie::<u32>::new();
+        assert_eq!((ternary.node_count(), patricia.node_count()), (0, 0));
+
+        // One node per character against one per shared run of characters
+        for (i, key) in ["application", "apply"].iter().enumerate() {
+            ternary.put(key.to_string(), i as u32);
+            patricia.put(key.to_string(), i as u32);
+        }
+        assert_eq!((ternary.node_count(), patricia.node_count()), (12, 3));
+        assert!(ternary.memory_usage() > 0 && patricia.memory_usage() > 0);
+
+        for (i, key) in ["she", "sells", "sea", "shells", "by", "été"]
+            .iter()
+            .enumerate()
+        {
+            ternary.put(key.to_string(), i as u32);
+            patricia.put(key.to_string(), i as u32);
+        }
+        check_for_each_entry(&ternary);
+        check_for_each_entry(&patricia);
+
+        ternary.clear();
+        patricia.clear();
+        assert_eq!((ternary.node_count(), patricia.node_count()), (0, 0));
+    }
 }
@@ -5,7 +5,10 @@
 //! the shared structure of the stored keys rather than probing one key at a
 //! time, and which a hash-backed table could not answer efficiently.
 //!
-//! Both traits are dyn-compatible:
+//! [`FuzzySearch`] answers approximate lookups the same way, pruning whole
+//! subtrees that cannot come within the allowed number of edits.
+//!
+//! All three traits are dyn-compatible:
 //!
 //! ```
 //! use tries::{PrefixSearch, SymbolTable, TernarySearchTrie};
@@ -66,6 +69,35 @@ pub trait SymbolTable<E: Clone> {
 
     /// Returns every stored key, in lexicographic order.
     fn get_all_keys(&self) -> Vec<String>;
+
+    /// Calls `f` with every key and its value, in lexicographic order of the
+    /// keys, stopping early once `f` returns `false`. Unlike
+    /// [`get_all_keys`](SymbolTable::get_all_keys), this need not hold every
+    /// key at once.
+    ///
+    /// ```
+    /// use tries::{SymbolTable, TernarySearchTrie};
+    ///
+    /// let mut table = TernarySearchTrie::<u32>::new();
+    /// for (i, word) in ["she", "sells", "sea", "shells"].iter().enumerate() {
+    ///     table.put(word.to_string(), i as u32);
+    /// }
+    ///
+    /// let mut first_two = Vec::new();
+    /// table.for_each_entry(&mut |key, value| {
+    ///     first_two.push((key.to_string(), *value));
+    ///     first_two.len() < 2
+    /// });
+    /// assert_eq!(first_two, [("sea".to_string(), 2), ("sells".to_string(), 1)]);
+    /// ```
+    fn for_each_entry(&self, f: &mut dyn FnMut(&str, &E) -> bool) {
+        for key in self.get_all_keys() {
+            let value = self.get(&key).expect("listed key is stored");
+            if !f(&key, &value) {
+                return;
+            }
+        }
+    }
 }
 
 /// Prefix queries over a string-keyed store.
@@ -95,3 +127,42 @@ pub trait PrefixSearch {
     /// for keys *extending* it.
     fn longest_prefix_of(&self, prefix: &str) -> Option<String>;
 }
+
+/// Approximate lookups over a string-keyed store.
+///
+/// # Examples
+///
+/// ```
+/// use tries::{FuzzySearch, SymbolTable, TernarySearchTrie};
+///
+/// let mut table = TernarySearchTrie::<u32>::new();
+/// for (i, word) in ["active", "actor", "inactive"].iter().enumerate() {
+///     table.put(word.to_string(), i as u32);
+/// }
+///
+/// assert_eq!(
+///     table.get_keys_within_distance("actve", 1),
+///     [("active".to_string(), 1)]
+/// );
+/// ```
+pub trait FuzzySearch {
+    /// Returns every stored key within `max_distance` Levenshtein edits
+    /// (insertions, deletions and substitutions of one character) of `key`,
+    /// paired with its distance, in lexicographic order of the keys.
+    fn get_keys_within_distance(&self, key: &str, max_distance: usize) -> Vec<(String, usize)>;
+}
+
+// One step of the Levenshtein dynamic program shared by the trie walks: given
+// the distances from a stored prefix to every prefix of `key`, returns them for
+// that stored prefix extended by `c`.
+pub(crate) fn next_distance_row(previous: &[usize], c: char, key: &[char]) -> Vec<usize> {
+    let mut row = Vec::with_capacity(previous.len());
+    row.push(previous[0] + 1);
+    for (i, &k) in key.iter().enumerate() {
+        let substitution = previous[i] + usize::from(k != c);
+        let deletion = previous[i + 1] + 1;
+        let insertion = row[i] + 1;
+        row.push(substitution.min(deletion).min(insertion));
+    }
+    row
+}
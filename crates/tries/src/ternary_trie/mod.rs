@@ -1,8 +1,10 @@
-use crate::symbol_table::{PrefixSearch, SymbolTable};
+use crate::symbol_table::{FuzzySearch, PrefixSearch, SymbolTable, next_distance_row};
 use std::cmp::Ordering;
 
+#[allow(clippy::module_inception)]
 mod tests;
 mod tests_integration;
+#[allow(clippy::module_inception)]
 mod tests_original;
 
 struct Node<E> {
@@ -31,6 +33,27 @@ impl<E> TernarySearchTrie<E> {
             size: 0,
         }
     }
+
+    /// Number of nodes, one per character position in the tree.
+    pub fn node_count(&self) -> usize {
+        Self::count_nodes(&self.root)
+    }
+
+    /// Approximate bytes held by the nodes, not counting allocator overhead.
+    pub fn memory_usage(&self) -> usize {
+        self.node_count() * std::mem::size_of::<Node<E>>()
+    }
+
+    fn count_nodes(node: &Option<Box<Node<E>>>) -> usize {
+        match node {
+            None => 0,
+            Some(node) => {
+                1 + Self::count_nodes(&node.left)
+                    + Self::count_nodes(&node.middle)
+                    + Self::count_nodes(&node.right)
+            }
+        }
+    }
 }
 
 impl<E: Clone> SymbolTable<E> for TernarySearchTrie<E> {
@@ -53,13 +76,7 @@ impl<E: Clone> SymbolTable<E> for TernarySearchTrie<E> {
         }
 
         let chars: Vec<char> = key.chars().collect();
-        let x = Self::get_recursive(&self.root, &chars, 0);
-
-        if x.is_some() {
-            Some(x.unwrap().clone())
-        } else {
-            None
-        }
+        Self::get_recursive(&self.root, &chars, 0).cloned()
     }
 
     fn delete(&mut self, key: &str) {
@@ -96,6 +113,10 @@ impl<E: Clone> SymbolTable<E> for TernarySearchTrie<E> {
         Self::collect_keys(&self.root, &mut result, &prefix);
         result
     }
+
+    fn for_each_entry(&self, f: &mut dyn FnMut(&str, &E) -> bool) {
+        Self::visit(&self.root, &mut String::new(), f);
+    }
 }
 
 impl<E: Clone> PrefixSearch for TernarySearchTrie<E> {
@@ -133,11 +154,30 @@ impl<E: Clone> PrefixSearch for TernarySearchTrie<E> {
         if length == 0 {
             None
         } else {
-            Some(prefix[0..length].to_string())
+            // `length` counts characters, so rebuild from them rather than
+            // slicing by byte offset
+            Some(chars[..length].iter().collect())
         }
     }
 }
 
+impl<E: Clone> FuzzySearch for TernarySearchTrie<E> {
+    fn get_keys_within_distance(&self, key: &str, max_distance: usize) -> Vec<(String, usize)> {
+        let chars: Vec<char> = key.chars().collect();
+        let first_row: Vec<usize> = (0..=chars.len()).collect();
+        let mut result = Vec::new();
+        Self::collect_within_distance(
+            &self.root,
+            &chars,
+            &first_row,
+            max_distance,
+            &mut String::new(),
+            &mut result,
+        );
+        result
+    }
+}
+
 impl<E: Clone> TernarySearchTrie<E> {
     // Helper function for inserting a key-value pair
     fn put_recursive(node: &mut Option<Box<Node<E>>>, key: &[char], value: E, pos: usize) -> bool {
@@ -218,30 +258,23 @@ impl<E: Clone> TernarySearchTrie<E> {
             return false;
         }
 
-        let mut was_deleted = false;
         let current_char = key[pos];
 
-        {
+        let was_deleted = {
             let current_node = node.as_mut().unwrap();
 
             match current_char.cmp(&current_node.c) {
-                Ordering::Less => {
-                    was_deleted = Self::delete_recursive(&mut current_node.left, key, pos);
-                }
-                Ordering::Greater => {
-                    was_deleted = Self::delete_recursive(&mut current_node.right, key, pos);
-                }
+                Ordering::Less => Self::delete_recursive(&mut current_node.left, key, pos),
+                Ordering::Greater => Self::delete_recursive(&mut current_node.right, key, pos),
                 Ordering::Equal => {
                     if pos == key.len() - 1 {
-                        was_deleted = current_node.value.is_some();
-                        current_node.value = None;
+                        current_node.value.take().is_some()
                     } else {
-                        was_deleted =
-                            Self::delete_recursive(&mut current_node.middle, key, pos + 1);
+                        Self::delete_recursive(&mut current_node.middle, key, pos + 1)
                     }
                 }
             }
-        }
+        };
 
         // Check if we can remove this node (no value and no children)
         let should_remove = if let Some(current_node) = node.as_ref() {
@@ -287,12 +320,37 @@ impl<E: Clone> TernarySearchTrie<E> {
         Self::collect_keys(&current_node.right, result, prefix);
     }
 
+    // Helper function for visiting every entry in order; returns false once
+    // `f` has asked to stop
+    fn visit(
+        node: &Option<Box<Node<E>>>,
+        prefix: &mut String,
+        f: &mut dyn FnMut(&str, &E) -> bool,
+    ) -> bool {
+        let Some(current_node) = node.as_ref() else {
+            return true;
+        };
+
+        if !Self::visit(&current_node.left, prefix, f) {
+            return false;
+        }
+
+        prefix.push(current_node.c);
+        let keep_going = match &current_node.value {
+            Some(value) if !f(prefix, value) => false,
+            _ => Self::visit(&current_node.middle, prefix, f),
+        };
+        prefix.pop();
+
+        keep_going && Self::visit(&current_node.right, prefix, f)
+    }
+
     // Helper function for finding a node that corresponds to a prefix
     fn find_prefix_node<'a>(
         node: &'a Option<Box<Node<E>>>,
         prefix: &[char],
         pos: usize,
-    ) -> Option<&'a Box<Node<E>>> {
+    ) -> Option<&'a Node<E>> {
         if node.is_none() || pos >= prefix.len() {
             return None;
         }
@@ -377,4 +435,50 @@ impl<E: Clone> TernarySearchTrie<E> {
             }
         }
     }
+
+    // Helper function for the fuzzy search. `row` holds the edit distances from
+    // `prefix` - the key spelled by the path above this node - to every prefix
+    // of `key`. Siblings on the left and right share that row; only the middle
+    // child extends the prefix.
+    fn collect_within_distance(
+        node: &Option<Box<Node<E>>>,
+        key: &[char],
+        row: &[usize],
+        max_distance: usize,
+        prefix: &mut String,
+        result: &mut Vec<(String, usize)>,
+    ) {
+        let Some(current_node) = node.as_ref() else {
+            return;
+        };
+
+        Self::collect_within_distance(&current_node.left, key, row, max_distance, prefix, result);
+
+        let next_row = next_distance_row(row, current_node.c, key);
+        prefix.push(current_node.c);
+
+        let distance = next_row[key.len()];
+        if current_node.value.is_some() && distance <= max_distance {
+            result.push((prefix.clone(), distance));
+        }
+
+        // No extension of the prefix can do better than the row's minimum
+        if next_row
+            .iter()
+            .min()
+            .is_some_and(|&min| min <= max_distance)
+        {
+            Self::collect_within_distance(
+                &current_node.middle,
+                key,
+                &next_row,
+                max_distance,
+                prefix,
+                result,
+            );
+        }
+        prefix.pop();
+
+        Self::collect_within_distance(&current_node.right, key, row, max_distance, prefix, result);
+    }
 }
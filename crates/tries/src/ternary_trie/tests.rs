@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use crate::{PrefixSearch, SymbolTable, TernarySearchTrie};
+    use crate::{FuzzySearch, PrefixSearch, SymbolTable, TernarySearchTrie};
 
     #[test]
     fn test_basic_operations() {
@@ -168,4 +168,37 @@ mod tests {
 
         assert_eq!(tst.get_size(), 3);
     }
+
+    #[test]
+    fn test_fuzzy_search() {
+        let mut tst = TernarySearchTrie::<i32>::new();
+        tst.put("user_123,active".to_string(), 1);
+        tst.put("user_124,active".to_string(), 2);
+        tst.put("user_123,inactive".to_string(), 3);
+        tst.put("admin".to_string(), 4);
+
+        assert_eq!(
+            tst.get_keys_within_distance("user_123,actve", 1),
+            [("user_123,active".to_string(), 1)]
+        );
+        assert_eq!(
+            tst.get_keys_within_distance("user_123,actve", 2),
+            [
+                ("user_123,active".to_string(), 1),
+                ("user_124,active".to_string(), 2)
+            ]
+        );
+        assert_eq!(
+            tst.get_keys_within_distance("admin", 0),
+            [("admin".to_string(), 0)]
+        );
+        assert!(tst.get_keys_within_distance("root", 1).is_empty());
+
+        // Distances count characters, not bytes
+        tst.put("héllo".to_string(), 5);
+        assert_eq!(
+            tst.get_keys_within_distance("hello", 1),
+            [("héllo".to_string(), 1)]
+        );
+    }
 }
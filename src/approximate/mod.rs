@@ -0,0 +1,162 @@
+//! `--approximate`: quick triage of huge files with a Bloom filter.
+//!
+//! Only the second file is held in memory, as a Bloom filter sized for the
+//! requested false-positive rate; the first file is streamed past it. A Bloom
+//! filter never forgets a line it was given, so every line it rejects is
+//! certainly missing from the second file. What it can do is wrongly accept a
+//! missing line, with about the configured probability, so the count of
+//! rejected lines is a lower bound and is also reported scaled up by that
+//! rate.
+//!
+//! Lines are counted per occurrence: a missing line repeated in the first
+//! file counts each time.
+
+use crate::fingerprint::fingerprint;
+use crate::line_source::LineSource;
+use std::io;
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    bit_count: u64,
+    hashes: u32,
+}
+
+impl BloomFilter {
+    /// Sizes a filter holding `items` entries with the given false-positive
+    /// rate.
+    pub fn with_rate(items: usize, false_positive_rate: f64) -> Self {
+        let items = items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let bit_count = (-items * false_positive_rate.ln() / (ln2 * ln2))
+            .ceil()
+            .max(64.0) as u64;
+        let hashes = ((bit_count as f64 / items) * ln2).round().clamp(1.0, 32.0) as u32;
+        BloomFilter {
+            bits: vec![0; bit_count.div_ceil(64) as usize],
+            bit_count,
+            hashes,
+        }
+    }
+
+    // The bit positions of `text`, derived from the two halves of its
+    // fingerprint by double hashing
+    fn positions(&self, text: &str) -> impl Iterator<Item = u64> + use<> {
+        let hash = fingerprint(text);
+        let (h1, h2) = (hash as u64, (hash >> 64) as u64);
+        let bit_count = self.bit_count;
+        (0..self.hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % bit_count)
+    }
+
+    pub fn insert(&mut self, text: &str) {
+        for position in self.positions(text) {
+            self.bits[(position / 64) as usize] |= 1 << (position % 64);
+        }
+    }
+
+    pub fn contains(&self, text: &str) -> bool {
+        self.positions(text)
+            .all(|position| self.bits[(position / 64) as usize] & (1 << (position % 64)) != 0)
+    }
+
+    /// The false-positive rate at the filter's actual load, which is below
+    /// the configured one when the sizing count included repeats.
+    pub fn current_false_positive_rate(&self) -> f64 {
+        let set_bits: u64 = self.bits.iter().map(|word| word.count_ones() as u64).sum();
+        (set_bits as f64 / self.bit_count as f64).powi(self.hashes as i32)
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        self.bits.len() * std::mem::size_of::<u64>()
+    }
+
+    pub fn hashes(&self) -> u32 {
+        self.hashes
+    }
+}
+
+/// The outcome of streaming the first file past the second's filter.
+pub struct Triage {
+    pub checked: usize,
+    pub missing: usize,
+    pub estimated_missing: usize,
+    pub sample: Vec<(u32, String)>,
+    pub false_positive_rate: f64,
+    pub filter_bytes: usize,
+    pub filter_hashes: u32,
+}
+
+fn normalize(text: &str, ignore_case: bool) -> String {
+    if ignore_case {
+        text.to_uppercase()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Builds a filter of `second` for `false_positive_rate` and checks every
+/// line of `first` against it, keeping the first `sample_size` distinct
+/// missing lines.
+pub fn triage(
+    first: &str,
+    second: &str,
+    ignore_case: bool,
+    false_positive_rate: f64,
+    sample_size: usize,
+) -> io::Result<Triage> {
+    if second == "-" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the second file is read twice and cannot be standard input",
+        ));
+    }
+
+    // One pass to size the filter, one to fill it
+    let mut items = 0;
+    LineSource::open(second)?.for_each_line(|_, line| {
+        if !line.is_empty() {
+            items += 1;
+        }
+    })?;
+    let mut filter = BloomFilter::with_rate(items, false_positive_rate);
+    LineSource::open(second)?.for_each_line(|_, line| {
+        if !line.is_empty() {
+            filter.insert(&normalize(line, ignore_case));
+        }
+    })?;
+
+    let mut checked = 0;
+    let mut missing = 0;
+    let mut sample: Vec<(u32, String)> = Vec::new();
+    LineSource::open(first)?.for_each_line(|index, line| {
+        if line.is_empty() {
+            return;
+        }
+        checked += 1;
+        let key = normalize(line, ignore_case);
+        if !filter.contains(&key) {
+            missing += 1;
+            if sample.len() < sample_size && !sample.iter().any(|(_, text)| *text == key) {
+                sample.push((index as u32, key));
+            }
+        }
+    })?;
+
+    // Each missing line slipped past the filter with the false-positive
+    // rate, so `missing` is about (1 - rate) of the true count
+    let current_rate = filter.current_false_positive_rate();
+    let estimated_missing =
+        ((missing as f64 / (1.0 - current_rate)).round() as usize).clamp(missing, checked);
+
+    Ok(Triage {
+        checked,
+        missing,
+        estimated_missing,
+        sample,
+        false_positive_rate: current_rate,
+        filter_bytes: filter.memory_bytes(),
+        filter_hashes: filter.hashes(),
+    })
+}
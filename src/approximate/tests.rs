@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use crate::approximate::{BloomFilter, triage};
+    use crate::test_util::write_temp;
+
+    #[test]
+    fn test_no_false_negatives() {
+        let mut filter = BloomFilter::with_rate(1000, 0.01);
+        for i in 0..1000 {
+            filter.insert(&format!("line {}", i));
+        }
+        assert!((0..1000).all(|i| filter.contains(&format!("line {}", i))));
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        let mut filter = BloomFilter::with_rate(10_000, 0.01);
+        for i in 0..10_000 {
+            filter.insert(&format!("present {}", i));
+        }
+        let false_positives = (0..10_000)
+            .filter(|i| filter.contains(&format!("absent {}", i)))
+            .count();
+        // Expect about 100; allow generous slack for the hash's randomness
+        assert!(false_positives < 250, "{} false positives", false_positives);
+        assert!(filter.memory_bytes() < 10_000 * 2);
+    }
+
+    #[test]
+    fn test_triage() {
+        let first = write_temp("first", "one\ntwo\n\nthree\nfour\nthree\nFive\n");
+        let second = write_temp("second", "two\nfour\nfive\n");
+        let (first, second) = (first.as_str(), second.as_str());
+
+        let result = triage(first, second, false, 0.001, 10).unwrap();
+        assert_eq!(result.checked, 6);
+        assert_eq!(result.missing, 4);
+        assert_eq!(
+            result.sample,
+            vec![
+                (0, "one".to_string()),
+                (3, "three".to_string()),
+                (6, "Five".to_string()),
+            ]
+        );
+
+        let result = triage(first, second, true, 0.001, 1).unwrap();
+        assert_eq!(result.missing, 3);
+        assert_eq!(result.sample, vec![(0, "ONE".to_string())]);
+    }
+}
@@ -0,0 +1,195 @@
+//! Reading the members of tar and zip archives in place of files.
+//!
+//! An input written `ARCHIVE:PATH`, where ARCHIVE is an existing `.tar`,
+//! `.tar.gz`, `.tgz` or `.zip` file, names the member PATH inside it. The
+//! member is decompressed into memory rather than extracted to disk; finding
+//! it in a compressed tarball means decompressing the archive up to it, as
+//! tarballs have no index, so where many members of a tarball are read they
+//! are read in one pass with [`for_each_tar_member`]. Member paths are written
+//! with `/` and without a leading `./`.
+
+use flate2::bufread::MultiGzDecoder;
+use std::fs::File;
+use std::io::{self, BufReader, Cursor, Read};
+use std::path::Path;
+use zip::ZipArchive;
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Tar,
+    TarGz,
+    Zip,
+}
+
+impl ArchiveKind {
+    /// The kind of archive `path` is named as, by its extension.
+    pub fn of(path: &str) -> Option<Self> {
+        let path = path.to_ascii_lowercase();
+        if path.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else if path.ends_with(".tar.gz") || path.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else if path.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether `path` is a regular file named as an archive.
+pub fn is_archive(path: &str) -> bool {
+    ArchiveKind::of(path).is_some() && Path::new(path).is_file()
+}
+
+/// Splits `ARCHIVE:PATH` into the archive and the member path, if the part
+/// before a `:` is an archive. Member paths may themselves hold `:`.
+pub fn split_member(input: &str) -> Option<(&str, &str)> {
+    input
+        .match_indices(':')
+        .map(|(colon, _)| (&input[..colon], &input[colon + 1..]))
+        .find(|(archive, member)| !member.is_empty() && is_archive(archive))
+}
+
+fn member_name(name: &str) -> &str {
+    name.strip_prefix("./").unwrap_or(name)
+}
+
+fn tar_archive(archive: &str, kind: ArchiveKind) -> io::Result<tar::Archive<Box<dyn Read>>> {
+    let file = BufReader::new(File::open(archive)?);
+    let reader: Box<dyn Read> = if kind == ArchiveKind::TarGz {
+        Box::new(MultiGzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    Ok(tar::Archive::new(reader))
+}
+
+fn not_an_archive(archive: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{} is not named as a tar or zip archive", archive),
+    )
+}
+
+/// Calls `f` with the path and a reader of the contents of every regular
+/// file of the tarball `archive`, in archive order. The tarball is read once,
+/// from start to end.
+pub fn for_each_tar_member<F>(archive: &str, mut f: F) -> io::Result<()>
+where
+    F: FnMut(&str, &mut dyn Read) -> io::Result<()>,
+{
+    let kind = match ArchiveKind::of(archive) {
+        Some(ArchiveKind::Zip) | None => return Err(not_an_archive(archive)),
+        Some(kind) => kind,
+    };
+    for entry in tar_archive(archive, kind)?.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let Some(name) = entry.path()?.to_str().map(str::to_string) else {
+            continue;
+        };
+        f(member_name(&name), &mut entry)?;
+    }
+    Ok(())
+}
+
+/// A zip archive whose members are read by path, in any order, through its
+/// central directory.
+pub struct ZipMembers {
+    archive: String,
+    zip: ZipArchive<File>,
+    // Each regular file's path, as members are named, paired with its name
+    // in the archive
+    names: Vec<(String, String)>,
+}
+
+impl ZipMembers {
+    pub fn open(archive: &str) -> io::Result<Self> {
+        let mut zip = ZipArchive::new(File::open(archive)?)?;
+        let mut names = Vec::new();
+        for index in 0..zip.len() {
+            let file = zip.by_index_raw(index)?;
+            if file.is_file() {
+                names.push((
+                    member_name(file.name()).to_string(),
+                    file.name().to_string(),
+                ));
+            }
+        }
+        Ok(ZipMembers {
+            archive: archive.to_string(),
+            zip,
+            names,
+        })
+    }
+
+    /// The paths of the regular files, in archive order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(|(member, _)| member.as_str())
+    }
+
+    /// A reader of the contents of `member`.
+    pub fn by_name(&mut self, member: &str) -> io::Result<impl Read + '_> {
+        let Some((_, name)) = self.names.iter().rev().find(|(path, _)| path == member) else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no member {} in {}", member, self.archive),
+            ));
+        };
+        Ok(self.zip.by_name(name)?)
+    }
+}
+
+/// The paths of the regular files in `archive`, in archive order.
+pub fn member_names(archive: &str) -> io::Result<Vec<String>> {
+    let mut names = Vec::new();
+    match ArchiveKind::of(archive).ok_or_else(|| not_an_archive(archive))? {
+        ArchiveKind::Zip => names.extend(ZipMembers::open(archive)?.names().map(str::to_string)),
+        _ => for_each_tar_member(archive, |name, _| {
+            names.push(name.to_string());
+            Ok(())
+        })?,
+    }
+    Ok(names)
+}
+
+/// The bytes of `member` of `archive`. A tarball holding the path more than
+/// once gives its last copy, as extracting it would.
+pub fn read_member(archive: &str, member: &str) -> io::Result<Vec<u8>> {
+    if ArchiveKind::of(archive) == Some(ArchiveKind::Zip) {
+        let mut bytes = Vec::new();
+        ZipMembers::open(archive)?
+            .by_name(member)?
+            .read_to_end(&mut bytes)?;
+        return Ok(bytes);
+    }
+
+    let mut contents = None;
+    for_each_tar_member(archive, |name, reader| {
+        if name == member {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes)?;
+            contents = Some(bytes);
+        }
+        Ok(())
+    })?;
+    contents.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no member {} in {}", member, archive),
+        )
+    })
+}
+
+/// Opens `input` if it names an archive member, or gives `None` for any
+/// other input.
+pub fn open_member(input: &str) -> Option<io::Result<Cursor<Vec<u8>>>> {
+    let (archive, member) = split_member(input)?;
+    Some(read_member(archive, member).map(Cursor::new))
+}
@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod tests {
+    use crate::archive::{ArchiveKind, member_names, read_member, split_member};
+    use crate::comparer::Comparer;
+    use crate::directory::{PathFilter, compare_directories};
+    use crate::line_source::LineSource;
+    use crate::test_util::{TempPath, temp_path};
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::{self, Write};
+    use tries::TernarySearchTrie;
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    fn tar_bytes(members: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, contents) in members {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, contents.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    // Writes `members` as the archive `name`, of the kind its extension gives
+    fn write_archive(name: &str, members: &[(&str, &str)]) -> TempPath {
+        let path = temp_path(name);
+        let bytes = match ArchiveKind::of(name).unwrap() {
+            ArchiveKind::Tar => tar_bytes(members),
+            ArchiveKind::TarGz => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(&tar_bytes(members)).unwrap();
+                encoder.finish().unwrap()
+            }
+            ArchiveKind::Zip => {
+                let mut zip = ZipWriter::new(io::Cursor::new(Vec::new()));
+                for (name, contents) in members {
+                    zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+                    zip.write_all(contents.as_bytes()).unwrap();
+                }
+                zip.finish().unwrap().into_inner()
+            }
+        };
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    fn lines(input: &str) -> Vec<String> {
+        let mut lines = Vec::new();
+        LineSource::open(input)
+            .unwrap()
+            .for_each_line(|_, line| lines.push(line.to_string()))
+            .unwrap();
+        lines
+    }
+
+    #[test]
+    fn test_archive_kind() {
+        assert_eq!(ArchiveKind::of("a.tar"), Some(ArchiveKind::Tar));
+        assert_eq!(ArchiveKind::of("a.TGZ"), Some(ArchiveKind::TarGz));
+        assert_eq!(ArchiveKind::of("a.tar.gz"), Some(ArchiveKind::TarGz));
+        assert_eq!(ArchiveKind::of("a.zip"), Some(ArchiveKind::Zip));
+        assert_eq!(ArchiveKind::of("a.gz"), None);
+    }
+
+    #[test]
+    fn test_members() {
+        let members = [("./bin/run.sh", "echo\n"), ("conf/app.ini", "a=1\nb=2\n")];
+        for name in ["members.tar", "members.tar.gz", "members.zip"] {
+            let archive = write_archive(name, &members);
+            assert_eq!(
+                member_names(archive.as_str()).unwrap(),
+                vec!["bin/run.sh", "conf/app.ini"]
+            );
+            assert_eq!(
+                read_member(archive.as_str(), "conf/app.ini").unwrap(),
+                b"a=1\nb=2\n"
+            );
+            let error = read_member(archive.as_str(), "conf/missing.ini").unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::NotFound);
+
+            let input = format!("{}:conf/app.ini", archive.as_str());
+            assert_eq!(
+                split_member(&input),
+                Some((archive.as_str(), "conf/app.ini"))
+            );
+            assert_eq!(lines(&input), vec!["a=1", "b=2"]);
+        }
+        assert_eq!(split_member(temp_path("absent.zip:a").as_str()), None);
+        assert_eq!(split_member("C:notes.txt"), None);
+    }
+
+    #[test]
+    fn test_last_copy_wins() {
+        let archive = write_archive("repeated.tar", &[("a.txt", "old\n"), ("a.txt", "new\n")]);
+        assert_eq!(read_member(archive.as_str(), "a.txt").unwrap(), b"new\n");
+
+        // Comparing loads the member's tables once, keeping the last copy
+        let other = write_archive("repeated-other.tar", &[("a.txt", "new\n")]);
+        let comparer = |first: String, second: String| Comparer::new(first, second);
+        let filter = PathFilter::new(&[], &[]).unwrap();
+        let comparison = compare_directories::<TernarySearchTrie<u32>>(
+            archive.as_str(),
+            other.as_str(),
+            true,
+            &filter,
+            &comparer,
+        )
+        .unwrap();
+        assert!(comparison.in_both[0].same_lines());
+    }
+
+    #[test]
+    fn test_compare_archives() {
+        let first = write_archive(
+            "release-1.tar.gz",
+            &[
+                ("README", "hello\n"),
+                ("lib/a.txt", "x\ny\n"),
+                ("lib/old.txt", "o\n"),
+            ],
+        );
+        let second = write_archive(
+            "release-2.zip",
+            &[
+                ("README", "hello\n"),
+                ("lib/a.txt", "y\nz\n"),
+                ("lib/new.txt", "n\n"),
+            ],
+        );
+
+        let comparer = |first: String, second: String| Comparer::new(first, second);
+        let filter = PathFilter::new(&[], &[]).unwrap();
+        let comparison = compare_directories::<TernarySearchTrie<u32>>(
+            first.as_str(),
+            second.as_str(),
+            true,
+            &filter,
+            &comparer,
+        )
+        .unwrap();
+        assert_eq!(comparison.only_in_first, vec!["lib/old.txt"]);
+        assert_eq!(comparison.only_in_second, vec!["lib/new.txt"]);
+        assert_eq!(comparison.in_both.len(), 2);
+        assert!(comparison.in_both[0].same_lines());
+        let changed = &comparison.in_both[1];
+        assert_eq!(changed.path, "lib/a.txt");
+        assert_eq!((changed.only_in_first(), changed.only_in_second()), (1, 1));
+
+        let top_level = compare_directories::<TernarySearchTrie<u32>>(
+            first.as_str(),
+            second.as_str(),
+            false,
+            &filter,
+            &comparer,
+        )
+        .unwrap();
+        assert_eq!(top_level.in_both.len(), 1);
+        assert!(top_level.only_in_first.is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+//! The symbol tables selectable with `--backend`, and the statistics
+//! reported by `--stats`.
+//!
+//! The tries implement [`SymbolTable`] and [`PrefixSearch`] themselves; the
+//! standard maps are wrapped here to honour the same contracts - keys in
+//! lexicographic order, and the empty string never stored - so the line
+//! comparison can be written once against [`LineTable`].
+
+use crate::clap_parser::Backend;
+use clap::ValueEnum;
+use std::collections::{BTreeMap, HashMap};
+use std::mem::size_of;
+use std::ops::Bound;
+use std::time::Duration;
+use tries::{PatriciaTrie, PrefixSearch, SymbolTable, TernarySearchTrie};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+/// Size figures of a built table.
+pub trait TableStats {
+    /// Nodes of a trie, or entries of a map.
+    fn node_count(&self) -> usize;
+
+    /// Approximate bytes held, not counting allocator overhead.
+    fn memory_usage(&self) -> usize;
+}
+
+/// Everything the line comparison needs of a symbol table.
+pub trait LineTable: SymbolTable<u32> + PrefixSearch + TableStats + Default + Send {}
+
+impl<T: SymbolTable<u32> + PrefixSearch + TableStats + Default + Send> LineTable for T {}
+
+impl<E> TableStats for TernarySearchTrie<E> {
+    fn node_count(&self) -> usize {
+        TernarySearchTrie::node_count(self)
+    }
+
+    fn memory_usage(&self) -> usize {
+        TernarySearchTrie::memory_usage(self)
+    }
+}
+
+impl<E> TableStats for PatriciaTrie<E> {
+    fn node_count(&self) -> usize {
+        PatriciaTrie::node_count(self)
+    }
+
+    fn memory_usage(&self) -> usize {
+        PatriciaTrie::memory_usage(self)
+    }
+}
+
+/// Prints the `--stats` of tables built together in `elapsed` to stderr,
+/// keeping the report itself unchanged.
+pub fn print_stats<T: LineTable>(backend: Backend, elapsed: Duration, tables: &[(&str, &T)]) {
+    eprintln!(
+        "BACKEND: {}, BUILD TIME: {:.3} ms",
+        backend
+            .to_possible_value()
+            .expect("no skipped variants")
+            .get_name(),
+        elapsed.as_secs_f64() * 1000.0
+    );
+    for (filename, table) in tables {
+        eprintln!(
+            "{}: {} keys, {} nodes, {} bytes",
+            filename,
+            table.get_size(),
+            table.node_count(),
+            table.memory_usage()
+        );
+    }
+}
+
+fn key_bytes<'a, I: Iterator<Item = &'a String>>(keys: I) -> usize {
+    keys.map(String::capacity).sum()
+}
+
+/// A [`HashMap`] behind the symbol table traits. Prefix queries scan every
+/// key.
+pub struct HashMapTable<E> {
+    map: HashMap<String, E>,
+}
+
+impl<E> Default for HashMapTable<E> {
+    fn default() -> Self {
+        HashMapTable {
+            map: HashMap::new(),
+        }
+    }
+}
+
+impl<E: Clone> SymbolTable<E> for HashMapTable<E> {
+    fn put(&mut self, key: String, value: E) {
+        if !key.is_empty() {
+            self.map.insert(key, value);
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<E> {
+        self.map.get(key).cloned()
+    }
+
+    fn delete(&mut self, key: &str) {
+        self.map.remove(key);
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    fn get_size(&self) -> usize {
+        self.map.len()
+    }
+
+    fn get_all_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.map.keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+}
+
+impl<E> PrefixSearch for HashMapTable<E> {
+    fn get_keys_with_prefix(&self, prefix: &str) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .map
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    fn longest_prefix_of(&self, prefix: &str) -> Option<String> {
+        longest_stored_prefix(prefix, |key| self.map.contains_key(key))
+    }
+}
+
+impl<E> TableStats for HashMapTable<E> {
+    fn node_count(&self) -> usize {
+        self.map.len()
+    }
+
+    fn memory_usage(&self) -> usize {
+        // One control byte per bucket alongside each slot
+        self.map.capacity() * (size_of::<(String, E)>() + 1) + key_bytes(self.map.keys())
+    }
+}
+
+/// A [`BTreeMap`] behind the symbol table traits.
+pub struct BTreeMapTable<E> {
+    map: BTreeMap<String, E>,
+}
+
+impl<E> Default for BTreeMapTable<E> {
+    fn default() -> Self {
+        BTreeMapTable {
+            map: BTreeMap::new(),
+        }
+    }
+}
+
+impl<E: Clone> SymbolTable<E> for BTreeMapTable<E> {
+    fn put(&mut self, key: String, value: E) {
+        if !key.is_empty() {
+            self.map.insert(key, value);
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<E> {
+        self.map.get(key).cloned()
+    }
+
+    fn delete(&mut self, key: &str) {
+        self.map.remove(key);
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    fn get_size(&self) -> usize {
+        self.map.len()
+    }
+
+    fn get_all_keys(&self) -> Vec<String> {
+        self.map.keys().cloned().collect()
+    }
+
+    fn for_each_entry(&self, f: &mut dyn FnMut(&str, &E) -> bool) {
+        for (key, value) in &self.map {
+            if !f(key, value) {
+                return;
+            }
+        }
+    }
+}
+
+impl<E> PrefixSearch for BTreeMapTable<E> {
+    fn get_keys_with_prefix(&self, prefix: &str) -> Vec<String> {
+        self.map
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(prefix))
+            .cloned()
+            .collect()
+    }
+
+    fn longest_prefix_of(&self, prefix: &str) -> Option<String> {
+        longest_stored_prefix(prefix, |key| self.map.contains_key(key))
+    }
+}
+
+impl<E> TableStats for BTreeMapTable<E> {
+    fn node_count(&self) -> usize {
+        self.map.len()
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.map.len() * size_of::<(String, E)>() + key_bytes(self.map.keys())
+    }
+}
+
+// Tries every prefix of `text` from the longest down, for maps that cannot
+// walk a shared path
+fn longest_stored_prefix<F: Fn(&str) -> bool>(text: &str, is_stored: F) -> Option<String> {
+    text.char_indices()
+        .map(|(i, c)| i + c.len_utf8())
+        .rev()
+        .map(|end| &text[..end])
+        .find(|prefix| is_stored(prefix))
+        .map(str::to_string)
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod tests {
+    use crate::backend::{BTreeMapTable, HashMapTable, LineTable, TableStats};
+    use tries::{PatriciaTrie, TernarySearchTrie};
+
+    const KEYS: &[&str] = &[
+        "she", "sells", "sea", "shells", "by", "the", "sea", "shore", "", "été", "étés",
+    ];
+
+    fn build<T: LineTable>() -> T {
+        let mut table = T::default();
+        for (index, key) in KEYS.iter().enumerate() {
+            table.put(key.to_string(), index as u32);
+        }
+        table
+    }
+
+    // Everything observable through the traits, to compare backends by
+    fn observe<T: LineTable>() -> Vec<String> {
+        let table = build::<T>();
+        let mut seen = vec![format!("size {}", table.get_size())];
+        for key in table.get_all_keys() {
+            seen.push(format!("{} = {:?}", key, table.get(&key)));
+        }
+        for prefix in ["", "s", "sh", "é", "x"] {
+            seen.push(format!("{:?}", table.get_keys_with_prefix(prefix)));
+        }
+        for text in ["shellsort", "seashore", "étésien", "s", ""] {
+            seen.push(format!("{:?}", table.longest_prefix_of(text)));
+        }
+        seen.push(format!("{:?}", table.get("")));
+        seen
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        let expected = observe::<TernarySearchTrie<u32>>();
+        assert_eq!(observe::<PatriciaTrie<u32>>(), expected);
+        assert_eq!(observe::<HashMapTable<u32>>(), expected);
+        assert_eq!(observe::<BTreeMapTable<u32>>(), expected);
+    }
+
+    #[test]
+    fn test_stats() {
+        let ternary = build::<TernarySearchTrie<u32>>();
+        let patricia = build::<PatriciaTrie<u32>>();
+        let hash_map = build::<HashMapTable<u32>>();
+        assert!(patricia.node_count() < ternary.node_count());
+        assert_eq!(hash_map.node_count(), 9);
+        assert!(hash_map.memory_usage() > 0);
+    }
+}
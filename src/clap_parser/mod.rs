@@ -1,18 +1,398 @@
-use clap::Parser;
+use crate::expr::{Expr, parse_expr};
+use crate::external::parse_size;
+use crate::stream::StreamOrder;
+use clap::{Parser, Subcommand, ValueEnum};
+use regex::Regex;
 
 #[derive(Parser, Debug)]
 #[command(
     author,
     version,
-    about = "File compare - fast compare lines in two files ignoring relative order"
+    about = "File compare - fast compare lines in two files ignoring relative order",
+    subcommand_negates_reqs = true
 )]
 pub struct Args {
-    #[arg(long, short)]
-    pub first: String,
-    #[arg(long, short)]
-    pub second: String,
-    #[arg(long, short, default_value = "false")]
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// File to compare, `-` for standard input, or `ARCHIVE:PATH` for a
+    /// member of a tar or zip archive. Two archives are compared member by
+    /// member
+    #[arg(long, short, required = true)]
+    pub first: Option<String>,
+    /// File to compare against, in any form `--first` takes, or a snapshot
+    #[arg(long, short, required = true)]
+    pub second: Option<String>,
+    #[arg(long, short, default_value = "false", global = true)]
     pub ignore_case: bool,
-    #[arg(long, short, default_value = "false")]
+    #[arg(long, short, default_value = "false", global = true)]
     pub render_html: bool,
+    /// What counts as one entry of the compared sets
+    #[arg(long, value_enum, default_value_t = Unit::Line)]
+    pub unit: Unit,
+    /// Regex splitting lines into words or tokens; defaults to `\W+` for
+    /// words and `\s+` for tokens
+    #[arg(long, value_parser = parse_regex)]
+    pub separator: Option<Regex>,
+    /// Number of characters per n-gram in `--unit char-ngram`
+    #[arg(long, default_value = "3", value_parser = clap::value_parser!(u32).range(1..))]
+    pub ngram_size: u32,
+    /// Compare in order, printing a unified diff instead of two line sets
+    #[arg(long, default_value = "false", conflicts_with = "unit")]
+    pub ordered: bool,
+    /// Unchanged lines shown around each change with `--ordered`
+    #[arg(long, default_value = "3")]
+    pub context: usize,
+    /// Also report lines present in both files but at different relative
+    /// positions
+    #[arg(long, default_value = "false", conflicts_with_all = ["unit", "ordered"])]
+    pub moves: bool,
+    /// Pair lines only in one file with their closest counterpart within N
+    /// edits, reporting them as changed
+    #[arg(long, value_name = "N", conflicts_with_all = ["unit", "ordered"])]
+    pub fuzzy: Option<usize>,
+    /// Instead of a report, print a patch adding the missing lines to the
+    /// second file
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        conflicts_with_all = ["unit", "ordered", "moves", "fuzzy", "render_html"]
+    )]
+    pub emit_patch: Option<PatchFormat>,
+    /// Make the patch also delete lines of the second file absent from the
+    /// first
+    #[arg(long, default_value = "false", requires = "emit_patch")]
+    pub remove_extras: bool,
+    /// Common ancestor of both files: classify lines as added, removed or
+    /// conflicting on either side
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["unit", "ordered", "moves", "fuzzy", "emit_patch"]
+    )]
+    pub base: Option<String>,
+    /// With `--base`, also write the merge of both sides to FILE
+    #[arg(long, value_name = "FILE", requires = "base")]
+    pub merged: Option<String>,
+    /// How a line of the first file is matched against the second
+    #[arg(
+        long = "match",
+        value_enum,
+        default_value_t = MatchMode::Exact,
+        conflicts_with_all = ["unit", "ordered", "moves", "fuzzy", "emit_patch", "base"]
+    )]
+    pub match_mode: MatchMode,
+    /// Present each list of differences as a tree of common prefixes, with
+    /// counts per directory or namespace
+    #[arg(
+        long,
+        default_value = "false",
+        conflicts_with_all = ["unit", "ordered", "moves", "fuzzy", "emit_patch", "base", "match_mode"]
+    )]
+    pub group_by_prefix: bool,
+    /// Characters ending one level of the `--group-by-prefix` tree; use `.`
+    /// for dotted config keys
+    #[arg(long, default_value = "/", requires = "group_by_prefix")]
+    pub group_separators: String,
+    /// Treat lines of the second file as patterns that lines of the first
+    /// must match; lines without pattern syntax still match literally
+    #[arg(
+        long,
+        value_enum,
+        value_name = "SYNTAX",
+        conflicts_with_all = [
+            "unit", "ordered", "moves", "fuzzy", "emit_patch", "base", "match_mode",
+            "group_by_prefix"
+        ]
+    )]
+    pub patterns: Option<PatternSyntax>,
+    /// Compare within about SIZE of memory (e.g. `512M`, `2G`) by sorting
+    /// runs of lines on disk; for files larger than RAM
+    #[arg(
+        long,
+        value_name = "SIZE",
+        value_parser = parse_size,
+        conflicts_with_all = [
+            "unit", "ordered", "moves", "fuzzy", "emit_patch", "base", "match_mode",
+            "group_by_prefix", "patterns"
+        ]
+    )]
+    pub memory_limit: Option<usize>,
+    /// Key lines by a 128-bit fingerprint instead of their text, for files of
+    /// very long lines; reported lines are re-read from the files
+    #[arg(
+        long,
+        default_value = "false",
+        conflicts_with_all = [
+            "unit", "ordered", "moves", "fuzzy", "emit_patch", "base", "match_mode",
+            "group_by_prefix", "patterns", "memory_limit"
+        ]
+    )]
+    pub hash: bool,
+    /// With `--hash`, re-read matched lines to rule out fingerprint collisions
+    #[arg(long, default_value = "false", requires = "hash")]
+    pub verify: bool,
+    /// Quick triage: count and sample lines of the first file missing from
+    /// the second using a Bloom filter, in a fraction of the memory
+    #[arg(
+        long,
+        default_value = "false",
+        conflicts_with_all = [
+            "unit", "ordered", "moves", "fuzzy", "emit_patch", "base", "match_mode",
+            "group_by_prefix", "patterns", "memory_limit", "hash"
+        ]
+    )]
+    pub approximate: bool,
+    /// Chance that `--approximate` takes a missing line for a present one
+    #[arg(
+        long,
+        value_name = "RATE",
+        default_value = "0.01",
+        value_parser = parse_rate,
+        requires = "approximate"
+    )]
+    pub false_positive_rate: f64,
+    /// Missing lines listed by `--approximate`
+    #[arg(long, value_name = "N", default_value = "10", requires = "approximate")]
+    pub sample: usize,
+    /// Threads for loading the files; defaults to the number of CPUs
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub threads: Option<u32>,
+    /// Symbol table holding the lines, `ternary` unless given. Only the line
+    /// comparison and `dir` take one; every other mode uses a ternary trie
+    #[arg(
+        long,
+        value_enum,
+        conflicts_with_all = ["unit", "ordered", "base", "memory_limit", "hash", "approximate"]
+    )]
+    pub backend: Option<Backend>,
+    /// Print build time, node count and memory use of each table to stderr
+    #[arg(long, default_value = "false")]
+    pub stats: bool,
+    /// Print differences as they are found instead of collecting and sorting
+    /// them first; in key order, or by line with `--memory-limit`
+    #[arg(
+        long,
+        default_value = "false",
+        conflicts_with_all = [
+            "unit", "ordered", "moves", "fuzzy", "emit_patch", "base", "match_mode",
+            "group_by_prefix", "patterns", "hash", "approximate"
+        ]
+    )]
+    pub stream: bool,
+    /// With `--stream`, still order by line number, sorting within about SIZE
+    /// of memory (e.g. `64M`) and spilling to disk beyond it
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, requires = "stream")]
+    pub sort_memory: Option<usize>,
+    /// Stop after N differences per side; without `--stream` the N lowest
+    /// line numbers are shown
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = [
+            "unit", "ordered", "moves", "fuzzy", "emit_patch", "base", "match_mode",
+            "group_by_prefix", "patterns", "hash", "approximate"
+        ]
+    )]
+    pub limit: Option<usize>,
+    /// Print only the line counts and similarity of both files
+    #[arg(
+        long,
+        default_value = "false",
+        conflicts_with_all = [
+            "unit", "ordered", "moves", "fuzzy", "emit_patch", "base", "match_mode",
+            "group_by_prefix", "patterns", "approximate", "stream", "limit"
+        ]
+    )]
+    pub summary_only: bool,
+    /// Compare again whenever either file changes, redrawing the report with
+    /// what changed since the previous run
+    #[arg(
+        long,
+        default_value = "false",
+        conflicts_with_all = [
+            "unit", "ordered", "emit_patch", "base", "match_mode", "group_by_prefix",
+            "patterns", "approximate", "stream", "limit", "render_html"
+        ]
+    )]
+    pub watch: bool,
+}
+
+impl Args {
+    // Both files are required unless a subcommand is given, and every
+    // subcommand is dispatched before these are used
+    pub fn first(&self) -> &str {
+        self.first.as_deref().expect("--first is required")
+    }
+
+    pub fn second(&self) -> &str {
+        self.second.as_deref().expect("--second is required")
+    }
+
+    /// Whether differences go through the streaming renderer.
+    pub fn streaming(&self) -> bool {
+        self.stream || self.limit.is_some()
+    }
+
+    /// Line order for streamed differences: sorted unless `--stream` was
+    /// given without `--sort-memory`.
+    pub fn stream_order(&self) -> StreamOrder {
+        match (self.stream, self.sort_memory) {
+            (true, None) => StreamOrder::Unsorted,
+            // Only reached with `--limit`, which keeps at most N + 1 lines
+            (false, _) => StreamOrder::ByLine(0),
+            (true, Some(budget)) => StreamOrder::ByLine(budget),
+        }
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend.unwrap_or(Backend::Ternary)
+    }
+
+    pub fn threads(&self) -> usize {
+        match self.threads {
+            Some(threads) => threads as usize,
+            None => std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Evaluate a set expression over the lines of files, e.g.
+    /// `(a.txt | b.txt) - c.txt & d.txt`
+    ///
+    /// Operators from loosest to tightest: `|` union, `^` symmetric
+    /// difference, `&` intersection, `-` difference.
+    Expr {
+        #[arg(value_parser = parse_expr)]
+        expression: Expr,
+    },
+    /// Print the lines of INPUT whose key is listed in LIST, in their
+    /// original order, like `grep -Fx -f LIST INPUT`
+    Filter {
+        /// File of keys, one per line
+        #[arg(long, short)]
+        list: String,
+        /// Print the lines whose key is not listed instead
+        #[arg(long, short = 'v', default_value = "false")]
+        exclude: bool,
+        /// Match on this one-based field of each line instead of the whole
+        /// line
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        key_field: Option<u32>,
+        /// Field delimiter for `--key-field`
+        #[arg(long, default_value = ",", requires = "key_field")]
+        delimiter: String,
+        /// File to filter, or `-` for standard input
+        input: String,
+    },
+    /// Track each line across versions of a file, oldest first: when it
+    /// appeared, when it disappeared and whether it came back
+    History {
+        /// The versions, as text files or snapshots
+        #[arg(required = true, num_args = 2..)]
+        files: Vec<String>,
+        /// Print the timeline as JSON
+        #[arg(long, default_value = "false", conflicts_with = "render_html")]
+        json: bool,
+    },
+    /// Compare two directories or archives: the files only one of them
+    /// holds, and the lines of each file both hold
+    Dir {
+        first: String,
+        second: String,
+        /// Descend into subdirectories
+        #[arg(long, short = 'R', default_value = "false")]
+        recursive: bool,
+        /// Only compare files matching this glob; a glob without `/` matches
+        /// the file name at any depth. May be repeated
+        #[arg(long, value_name = "GLOB")]
+        include: Vec<String>,
+        /// Skip files and directories matching this glob. May be repeated
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+    },
+    /// Save the symbol table of a file, so `--second` can load it instead
+    /// of rebuilding it on every comparison
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SnapshotCommand {
+    /// Build the table of INPUT and write it to OUTPUT; pass `-i` here if
+    /// the comparisons will use it
+    Create {
+        /// File to snapshot
+        input: String,
+        /// Snapshot file to write
+        #[arg(long, short)]
+        output: String,
+    },
+    /// Print the normalization, source checksum and counts of a snapshot
+    Info { snapshot: String },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Unit {
+    /// Whole lines
+    Line,
+    /// Runs of word characters
+    Word,
+    /// Whitespace-separated tokens
+    Token,
+    /// Overlapping character n-grams of each line
+    CharNgram,
+}
+
+fn parse_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    Regex::new(pattern)
+}
+
+fn parse_rate(rate: &str) -> Result<f64, String> {
+    match rate.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate < 1.0 => Ok(rate),
+        _ => Err(format!("'{}' is not a rate between 0 and 1", rate)),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum MatchMode {
+    /// Lines match when equal
+    Exact,
+    /// A line is covered when some line of the second file is a prefix of it
+    Prefix,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum PatternSyntax {
+    /// `*` and `?` within a path segment, `**` across them, `[...]` classes
+    Glob,
+    /// Regular expressions, each matching a whole line
+    Regex,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// Ternary search trie: one node per character
+    Ternary,
+    /// Patricia trie: one node per shared run of characters
+    Patricia,
+    /// Hash map: fastest lookups, prefix queries scan every key
+    #[value(name = "hashmap")]
+    HashMap,
+    /// B-tree map: sorted keys without a trie
+    #[value(name = "btreemap")]
+    BTreeMap,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum PatchFormat {
+    /// A unified diff for `patch`
+    Unified,
+    /// A script for `ed`, as written by `diff -e`
+    Ed,
 }
@@ -0,0 +1,787 @@
+//! The `file_compare` command line tool: dispatching parsed [`Args`] to the
+//! comparison they select and printing its report.
+
+use crate::approximate::triage;
+use crate::archive::{is_archive, split_member};
+use crate::backend::{BTreeMapTable, HashMapTable, LineTable, print_stats};
+use crate::clap_parser::{
+    Args, Backend, Command, MatchMode, PatchFormat, PatternSyntax, SnapshotCommand, Unit,
+};
+use crate::comparer::{Comparer, ComparisonResult, Strategy};
+use crate::directory::{PathFilter, compare_directories};
+use crate::expr::{Evaluator, Expr, result_lines};
+use crate::external::compare_files;
+use crate::filter::{KeySelector, LineFilter};
+use crate::history::build_timeline;
+use crate::keys_missing_from;
+use crate::line_source::FileLines;
+use crate::ordered_diff::{Hunk, Interner, build_hunks, diff};
+use crate::parallel::build_table;
+use crate::patch::{PatchPlan, read_all_lines};
+use crate::patterns::PatternSet;
+use crate::prefix_groups::group_by_prefix;
+use crate::prefix_match::match_prefixes;
+use crate::render::{
+    HtmlRenderer, HtmlStream, Renderer, TextRenderer, TextStream, render_ed_script,
+    render_html_coverage, render_html_directories, render_html_expr, render_html_grouped_output,
+    render_html_history, render_html_patterns, render_html_snapshot_info, render_html_three_way,
+    render_html_token_output, render_html_triage, render_html_unified_diff, render_json_history,
+    render_text_coverage, render_text_directories, render_text_expr, render_text_grouped_output,
+    render_text_history, render_text_patterns, render_text_snapshot_info, render_text_three_way,
+    render_text_token_output, render_text_triage, write_text_delta, write_unified_diff,
+};
+use crate::snapshot::{create_snapshot, is_snapshot, read_header};
+use crate::stream::{StreamRenderer, stream_differences, stream_external};
+use crate::summary::{Summary, summarize};
+use crate::three_way::{compare_three_way, write_merged};
+use crate::tokenizer::{Occurrence, Tokenizer, build_token_table};
+use crate::watch::{DEBOUNCE, Watcher, delta};
+use std::fs::File;
+use std::io::{self, IsTerminal, Write};
+use std::time::Instant;
+use tries::{PatriciaTrie, SymbolTable, TernarySearchTrie};
+
+/// Runs the command line tool for parsed `args`.
+pub fn run(args: &Args) {
+    if let Some(command) = &args.command {
+        if args.backend.is_some() && !matches!(command, Command::Dir { .. }) {
+            eprintln!("Cannot use --backend here: only the line comparison and dir take one");
+            std::process::exit(1);
+        }
+        match command {
+            Command::Expr { expression } => evaluate_expression(args, expression),
+            Command::Filter {
+                list,
+                exclude,
+                key_field,
+                delimiter,
+                input,
+            } => {
+                let key = match key_field {
+                    Some(index) => KeySelector::Field {
+                        index: *index as usize,
+                        delimiter: delimiter.clone(),
+                    },
+                    None => KeySelector::Line,
+                };
+                let filter = match LineFilter::new(list, key, args.ignore_case, *exclude) {
+                    Ok(filter) => filter,
+                    Err(error) => {
+                        eprintln!("Cannot read list {}: {}", list, error);
+                        std::process::exit(1);
+                    }
+                };
+                filter_lines(&filter, input);
+            }
+            Command::Snapshot { action } => snapshot(args, action),
+            Command::History { files, json } => history(args, files, *json),
+            Command::Dir {
+                first,
+                second,
+                recursive,
+                include,
+                exclude,
+            } => directories(args, first, second, *recursive, include, exclude),
+        }
+        return;
+    }
+
+    // Two archives are compared member by member, like two directories
+    if is_archive(args.first()) || is_archive(args.second()) {
+        if !is_archive(args.first()) || !is_archive(args.second()) {
+            eprintln!(
+                "Cannot compare {} and {}: an archive is compared with another archive; \
+                 name a member as ARCHIVE:PATH to compare its lines",
+                args.first(),
+                args.second()
+            );
+            std::process::exit(1);
+        }
+        directories(args, args.first(), args.second(), true, &[], &[]);
+        return;
+    }
+
+    // Only comparisons of two line tables can take a table instead of a file
+    if is_snapshot(args.second())
+        && (args.ordered
+            || args.base.is_some()
+            || args.unit != Unit::Line
+            || args.memory_limit.is_some()
+            || args.hash
+            || args.approximate
+            || args.patterns.is_some()
+            || args.emit_patch.is_some())
+    {
+        eprintln!(
+            "Cannot compare against snapshot {}: this mode reads the second file's lines",
+            args.second()
+        );
+        std::process::exit(1);
+    }
+
+    // The patch is written from the files' text, read again after comparing
+    if args.emit_patch.is_some() && (args.first() == "-" || args.second() == "-") {
+        eprintln!("Cannot emit a patch for standard input: the inputs are read twice");
+        std::process::exit(1);
+    }
+
+    if args.ordered {
+        compare_ordered(args);
+        return;
+    }
+
+    if let Some(base) = &args.base {
+        compare_with_base(args, base);
+        return;
+    }
+
+    if let Some(tokenizer) =
+        Tokenizer::new(args.unit, args.separator.clone(), args.ngram_size as usize)
+    {
+        compare_tokens(args, &tokenizer);
+        return;
+    }
+
+    if args.watch {
+        watch(args);
+        return;
+    }
+
+    if let Some(memory_limit) = args.memory_limit {
+        let comparison = compare_files(args.first(), args.second(), args.ignore_case, memory_limit)
+            .unwrap_or_else(|error| {
+                eprintln!(
+                    "Cannot compare {} and {}: {}",
+                    args.first(),
+                    args.second(),
+                    error
+                );
+                std::process::exit(1);
+            });
+        if args.summary_only {
+            write_summary(args, comparison.summary);
+            return;
+        }
+        // The differences are rendered as they are read back from disk, with
+        // or without `--stream`, so they are never all in memory
+        let summary = comparison.summary;
+        write_stream(args, Some(summary), |renderer| {
+            stream_external(comparison, args.limit, renderer)
+        });
+        return;
+    }
+
+    if args.hash {
+        let comparer = comparer(args).strategy(Strategy::Hashed {
+            verify: args.verify,
+        });
+        render_comparison(args, &comparer);
+        return;
+    }
+
+    if args.approximate {
+        let triage = triage(
+            args.first(),
+            args.second(),
+            args.ignore_case,
+            args.false_positive_rate,
+            args.sample,
+        )
+        .unwrap_or_else(|error| {
+            eprintln!(
+                "Cannot triage {} against {}: {}",
+                args.first(),
+                args.second(),
+                error
+            );
+            std::process::exit(1);
+        });
+        if args.render_html {
+            render_html_triage(args.first(), args.second(), &triage);
+        } else {
+            render_text_triage(args.first(), args.second(), &triage);
+        }
+        return;
+    }
+
+    match args.backend() {
+        Backend::Ternary => compare_lines::<TernarySearchTrie<u32>>(args),
+        Backend::Patricia => compare_lines::<PatriciaTrie<u32>>(args),
+        Backend::HashMap => compare_lines::<HashMapTable<u32>>(args),
+        Backend::BTreeMap => compare_lines::<BTreeMapTable<u32>>(args),
+    }
+}
+
+// The line comparison and the modes built on it, over any `--backend`
+fn compare_lines<T: LineTable>(args: &Args) {
+    let comparer = comparer(args);
+
+    if let Some(syntax) = args.patterns {
+        let started = Instant::now();
+        let (symbol_table_1, _): (T, usize) =
+            build_table(args.first(), args.ignore_case, args.threads()).unwrap_or_else(|error| {
+                eprintln!("Cannot read {}: {}", args.first(), error);
+                std::process::exit(1);
+            });
+        if args.stats {
+            print_stats(
+                args.backend(),
+                started.elapsed(),
+                &[(args.first(), &symbol_table_1)],
+            );
+        }
+        compare_with_patterns(args, syntax, &symbol_table_1);
+        return;
+    }
+
+    // TODO: handle duplicate keys
+    let started = Instant::now();
+    let (first, second) = comparer.build_tables::<T>().unwrap_or_else(|error| {
+        eprintln!(
+            "Cannot compare {} and {}: {}",
+            args.first(),
+            args.second(),
+            error
+        );
+        std::process::exit(1);
+    });
+    let (symbol_table_1, symbol_table_2) = (&first.symbol_table, &second.symbol_table);
+    if args.stats {
+        print_stats(
+            args.backend(),
+            started.elapsed(),
+            &[
+                (args.first(), symbol_table_1),
+                (args.second(), symbol_table_2),
+            ],
+        );
+    }
+
+    let summary = || {
+        summarize(
+            symbol_table_1,
+            first.total_lines,
+            symbol_table_2,
+            second.total_lines,
+        )
+    };
+
+    if args.summary_only {
+        write_summary(args, summary());
+        return;
+    }
+
+    if args.streaming() {
+        write_stream(args, Some(summary()), |renderer| {
+            stream_differences(
+                symbol_table_1,
+                symbol_table_2,
+                args.stream_order(),
+                args.limit,
+                renderer,
+            )
+        });
+        return;
+    }
+
+    if args.match_mode == MatchMode::Prefix {
+        let coverage = match_prefixes(symbol_table_1, symbol_table_2);
+        if args.render_html {
+            render_html_coverage(args.first(), args.second(), &coverage);
+        } else {
+            render_text_coverage(args.first(), args.second(), &coverage);
+        }
+        return;
+    }
+
+    let result = comparer.compare_tables(&first, &second);
+
+    if args.group_by_prefix {
+        let groups_in_first_not_in_second =
+            group_by_prefix(&result.only_in_first, &args.group_separators);
+        let groups_in_second_not_in_first =
+            group_by_prefix(&result.only_in_second, &args.group_separators);
+        let render = if args.render_html {
+            render_html_grouped_output
+        } else {
+            render_text_grouped_output
+        };
+        write_report(|out| {
+            render(
+                out,
+                args.first(),
+                args.second(),
+                result.summary(),
+                &groups_in_first_not_in_second,
+                &groups_in_second_not_in_first,
+            )
+        });
+        return;
+    }
+
+    if let Some(format) = args.emit_patch {
+        emit_patch(args, format, &result.only_in_first, symbol_table_1);
+        return;
+    }
+
+    write_result(args, &result);
+}
+
+// The comparison `args` describe, before a strategy is chosen
+fn comparer(args: &Args) -> Comparer {
+    Comparer::new(args.first(), args.second())
+        .ignore_case(args.ignore_case)
+        .backend(args.backend())
+        .threads(args.threads())
+        .moves(args.moves)
+        .fuzzy(args.fuzzy)
+}
+
+fn render_comparison(args: &Args, comparer: &Comparer) {
+    match comparer.compare() {
+        Ok(result) if args.summary_only => write_summary(args, result.summary()),
+        Ok(result) => write_result(args, &result),
+        Err(error) => {
+            eprintln!(
+                "Cannot compare {} and {}: {}",
+                args.first(),
+                args.second(),
+                error
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+// `--watch`: compares, then waits for a change to either file and compares
+// again, until interrupted
+fn watch(args: &Args) {
+    if args.first() == "-" || args.second() == "-" {
+        eprintln!("Cannot watch standard input");
+        std::process::exit(1);
+    }
+    let strategy = match (args.memory_limit, args.hash) {
+        (Some(memory_limit), _) => Strategy::External { memory_limit },
+        (None, true) => Strategy::Hashed {
+            verify: args.verify,
+        },
+        (None, false) => Strategy::InMemory,
+    };
+    let comparer = comparer(args).strategy(strategy);
+    // A member changes with its archive
+    let watched = |input| split_member(input).map_or(input, |(archive, _)| archive);
+    let mut watcher = Watcher::new(&[watched(args.first()), watched(args.second())])
+        .unwrap_or_else(|error| {
+            eprintln!(
+                "Cannot watch {} and {}: {}",
+                args.first(),
+                args.second(),
+                error
+            );
+            std::process::exit(1);
+        });
+
+    let mut previous: Option<ComparisonResult> = None;
+    for run in 1.. {
+        match comparer.compare() {
+            Ok(result) => {
+                redraw(args, run, previous.as_ref(), &result);
+                previous = Some(result);
+            }
+            // A file may be mid-rewrite; the next change tries again
+            Err(error) => eprintln!(
+                "Cannot compare {} and {}: {}",
+                args.first(),
+                args.second(),
+                error
+            ),
+        }
+        if let Err(error) = watcher.wait(DEBOUNCE) {
+            eprintln!("Cannot watch {} and {}: {}", args.first(), args.second(), error);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Replaces the screen with the report of `result`, headed by how it differs
+// from the previous run's
+fn redraw(args: &Args, run: usize, previous: Option<&ComparisonResult>, result: &ComparisonResult) {
+    let deltas = previous.map(|previous| {
+        (
+            delta(&previous.only_in_first, &result.only_in_first),
+            delta(&previous.only_in_second, &result.only_in_second),
+        )
+    });
+    // Rendered whole before the screen is cleared, so it does not flicker
+    let mut report = Vec::new();
+    write_text_delta(&mut report, run, args.first(), args.second(), deltas)
+        .and_then(|_| TextRenderer.render(result, &mut report))
+        .expect("writing to memory cannot fail");
+
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    let clear = if output.is_terminal() {
+        "\x1b[2J\x1b[H"
+    } else {
+        ""
+    };
+    match write!(output, "{}", clear)
+        .and_then(|_| output.write_all(&report))
+        .and_then(|_| output.flush())
+    {
+        Ok(_) => {}
+        // The reader went away, as with `| head`
+        Err(error) if error.kind() == io::ErrorKind::BrokenPipe => std::process::exit(0),
+        Err(error) => {
+            eprintln!("Cannot write report: {}", error);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn write_result(args: &Args, result: &ComparisonResult) {
+    let renderer: &dyn Renderer = if args.render_html {
+        &HtmlRenderer
+    } else {
+        &TextRenderer
+    };
+    write_report(|out| renderer.render(result, out));
+}
+
+// Writes a report to stdout through `write`
+fn write_report<F>(write: F)
+where
+    F: FnOnce(&mut dyn Write) -> io::Result<()>,
+{
+    let stdout = io::stdout();
+    let mut output = io::BufWriter::new(stdout.lock());
+    match write(&mut output).and_then(|_| output.flush()) {
+        Ok(_) => {}
+        // The reader went away, as with `| head`
+        Err(error) if error.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(error) => {
+            eprintln!("Cannot write report: {}", error);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn write_patch(first: &str, second: &str, old: &FileLines, new: &FileLines, hunks: &[Hunk]) {
+    let stdout = io::stdout();
+    let mut output = io::BufWriter::new(stdout.lock());
+    match write_unified_diff(&mut output, first, second, old, new, hunks)
+        .and_then(|_| output.flush())
+    {
+        Ok(_) => {}
+        // The reader went away, as with `| head`
+        Err(error) if error.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(error) => {
+            eprintln!("Cannot write patch: {}", error);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Hands `stream` the renderer for `args`, headed by `summary` if given and
+// writing straight to stdout so each difference shows up as soon as it is
+// found
+fn write_stream<F>(args: &Args, summary: Option<Summary>, stream: F)
+where
+    F: FnOnce(&mut dyn StreamRenderer) -> io::Result<()>,
+{
+    let stdout = io::stdout();
+    let output = stdout.lock();
+    let result = if args.render_html {
+        let mut renderer = HtmlStream::new(output, args.first(), args.second());
+        if let Some(summary) = summary {
+            renderer = renderer.with_summary(summary);
+        }
+        stream(&mut renderer)
+    } else {
+        let mut renderer = TextStream::new(output, args.first(), args.second());
+        if let Some(summary) = summary {
+            renderer = renderer.with_summary(summary);
+        }
+        stream(&mut renderer)
+    };
+    match result {
+        Ok(_) => {}
+        // The reader went away, as with `| head`
+        Err(error) if error.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(error) => {
+            eprintln!(
+                "Cannot compare {} and {}: {}",
+                args.first(),
+                args.second(),
+                error
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+// `--summary-only`: a report headed by the summary, with no sides
+fn write_summary(args: &Args, summary: Summary) {
+    write_stream(args, Some(summary), |renderer| {
+        renderer.start()?;
+        renderer.finish()
+    });
+}
+
+fn history(args: &Args, files: &[String], json: bool) {
+    let timeline = build_timeline(files, args.ignore_case).unwrap_or_else(|error| {
+        eprintln!("Cannot build history: {}", error);
+        std::process::exit(1);
+    });
+    if json {
+        render_json_history(&timeline);
+    } else if args.render_html {
+        render_html_history(&timeline);
+    } else {
+        render_text_history(&timeline);
+    }
+}
+
+fn directories(
+    args: &Args,
+    first: &str,
+    second: &str,
+    recursive: bool,
+    include: &[String],
+    exclude: &[String],
+) {
+    let filter = PathFilter::new(include, exclude).unwrap_or_else(|error| {
+        eprintln!("Cannot compile glob: {}", error);
+        std::process::exit(1);
+    });
+    let file_comparer = |first_file: String, second_file: String| {
+        Comparer::new(first_file, second_file)
+            .ignore_case(args.ignore_case)
+            .threads(args.threads())
+    };
+    let compare = match args.backend() {
+        Backend::Ternary => compare_directories::<TernarySearchTrie<u32>>,
+        Backend::Patricia => compare_directories::<PatriciaTrie<u32>>,
+        Backend::HashMap => compare_directories::<HashMapTable<u32>>,
+        Backend::BTreeMap => compare_directories::<BTreeMapTable<u32>>,
+    };
+    let comparison =
+        compare(first, second, recursive, &filter, &file_comparer).unwrap_or_else(|error| {
+            eprintln!("Cannot compare {} and {}: {}", first, second, error);
+            std::process::exit(1);
+        });
+    if args.render_html {
+        render_html_directories(&comparison);
+    } else {
+        render_text_directories(&comparison);
+    }
+}
+
+fn snapshot(args: &Args, action: &SnapshotCommand) {
+    match action {
+        SnapshotCommand::Create { input, output } => {
+            if let Err(error) = create_snapshot(input, output, args.ignore_case) {
+                eprintln!("Cannot create snapshot {} of {}: {}", output, input, error);
+                std::process::exit(1);
+            }
+        }
+        SnapshotCommand::Info { snapshot } => {
+            let header = File::open(snapshot)
+                .and_then(|file| read_header(&mut io::BufReader::new(file)))
+                .unwrap_or_else(|error| {
+                    eprintln!("Cannot read snapshot {}: {}", snapshot, error);
+                    std::process::exit(1);
+                });
+            if args.render_html {
+                render_html_snapshot_info(snapshot, &header);
+            } else {
+                render_text_snapshot_info(snapshot, &header);
+            }
+        }
+    }
+}
+
+fn filter_lines(filter: &LineFilter, input: &str) {
+    let stdout = io::stdout();
+    let mut output = io::BufWriter::new(stdout.lock());
+    let result = if input == "-" {
+        filter.filter(io::stdin().lock(), &mut output)
+    } else {
+        File::open(input).and_then(|file| filter.filter(io::BufReader::new(file), &mut output))
+    };
+
+    match result {
+        Ok(_) => {}
+        // The reader went away, as with `| head`
+        Err(error) if error.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(error) => {
+            eprintln!("Cannot filter {}: {}", input, error);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn evaluate_expression(args: &Args, expression: &Expr) {
+    let mut evaluator = Evaluator::new(args.ignore_case);
+    let result = evaluator.evaluate(expression).unwrap_or_else(|error| {
+        eprintln!("Cannot evaluate expression: {}", error);
+        std::process::exit(1);
+    });
+    let lines = result_lines(&result);
+
+    if args.render_html {
+        render_html_expr(&evaluator.files, &lines);
+    } else {
+        render_text_expr(&evaluator.files, &lines);
+    }
+}
+
+fn compare_with_base(args: &Args, base: &str) {
+    let (comparison, first_lines, second_lines) =
+        compare_three_way(base, args.first(), args.second(), args.ignore_case).unwrap_or_else(
+            |error| {
+                eprintln!(
+                    "Cannot compare {} and {} against {}: {}",
+                    args.first(),
+                    args.second(),
+                    base,
+                    error
+                );
+                std::process::exit(1);
+            },
+        );
+    let report = comparison.report();
+
+    if let Some(merged) = &args.merged {
+        let merged_lines = comparison.merge(&first_lines, &second_lines);
+        if let Err(error) = write_merged(merged, &merged_lines) {
+            eprintln!("Cannot write merged file {}: {}", merged, error);
+            std::process::exit(1);
+        }
+    }
+
+    if args.render_html {
+        render_html_three_way(base, args.first(), args.second(), &report);
+    } else {
+        render_text_three_way(base, args.first(), args.second(), &report);
+    }
+}
+
+fn compare_tokens(args: &Args, tokenizer: &Tokenizer) {
+    let build = |filename: &str| {
+        let mut symbol_table = TernarySearchTrie::<Occurrence>::new();
+        if let Err(error) =
+            build_token_table(filename, tokenizer, &mut symbol_table, args.ignore_case)
+        {
+            eprintln!("Cannot read {}: {}", filename, error);
+            std::process::exit(1);
+        }
+        symbol_table
+    };
+    let symbol_table_1 = build(args.first());
+    let symbol_table_2 = build(args.second());
+
+    let mut tokens_in_first_not_in_second = keys_missing_from(&symbol_table_1, &symbol_table_2);
+    let mut tokens_in_second_not_in_first = keys_missing_from(&symbol_table_2, &symbol_table_1);
+
+    tokens_in_first_not_in_second.sort_by_key(|k| (k.0.line, k.0.column));
+    tokens_in_second_not_in_first.sort_by_key(|k| (k.0.line, k.0.column));
+
+    let render = if args.render_html {
+        render_html_token_output
+    } else {
+        render_text_token_output
+    };
+    write_report(|out| {
+        render(
+            out,
+            args.first(),
+            args.second(),
+            &tokens_in_first_not_in_second,
+            &tokens_in_second_not_in_first,
+        )
+    });
+}
+
+fn compare_ordered(args: &Args) {
+    let mut interner = Interner::new(args.ignore_case);
+    let mut intern_file = |filename: &str| {
+        interner.intern_file(filename).unwrap_or_else(|error| {
+            eprintln!("Cannot read {}: {}", filename, error);
+            std::process::exit(1);
+        })
+    };
+    let old = intern_file(args.first());
+    let new = intern_file(args.second());
+
+    let edits = diff(&old.ids, &new.ids);
+    let hunks = build_hunks(&edits, args.context);
+
+    if args.render_html {
+        render_html_unified_diff(args.first(), args.second(), &old.text, &new.text, &hunks);
+    } else {
+        write_patch(args.first(), args.second(), &old.text, &new.text, &hunks);
+    }
+}
+
+fn compare_with_patterns<T: SymbolTable<u32>>(
+    args: &Args,
+    syntax: PatternSyntax,
+    symbol_table_1: &T,
+) {
+    let patterns = read_all_lines(args.second()).unwrap_or_else(|error| {
+        eprintln!("Cannot read {}: {}", args.second(), error);
+        std::process::exit(1);
+    });
+    let pattern_set = match PatternSet::new(&patterns, syntax, args.ignore_case) {
+        Ok(pattern_set) => pattern_set,
+        Err(error) => {
+            eprintln!("Invalid pattern in {}: {}", args.second(), error);
+            std::process::exit(1);
+        }
+    };
+    let report = pattern_set.match_lines(symbol_table_1);
+
+    if args.render_html {
+        render_html_patterns(args.first(), args.second(), &report);
+    } else {
+        render_text_patterns(args.first(), args.second(), &report);
+    }
+}
+
+fn emit_patch<T: SymbolTable<u32>>(
+    args: &Args,
+    format: PatchFormat,
+    words_in_first_not_in_second: &[(u32, String)],
+    symbol_table_1: &T,
+) {
+    let read = |filename: &str| {
+        FileLines::read(filename).unwrap_or_else(|error| {
+            eprintln!("Cannot read {}: {}", filename, error);
+            std::process::exit(1);
+        })
+    };
+    let first_lines = read(args.first()).lines;
+    let second = read(args.second());
+    let plan = PatchPlan::new(
+        &first_lines,
+        &second,
+        words_in_first_not_in_second,
+        symbol_table_1,
+        args.ignore_case,
+        args.remove_extras,
+    )
+    .unwrap_or_else(|error| {
+        eprintln!("Cannot emit patch for {}: {}", args.second(), error);
+        std::process::exit(1);
+    });
+
+    match format {
+        PatchFormat::Unified => {
+            let hunks = build_hunks(&plan.edits(), args.context);
+            let patched = plan.patched(&second);
+            write_patch(args.second(), args.second(), &second, &patched, &hunks);
+        }
+        PatchFormat::Ed => write_report(|out| render_ed_script(out, &plan)),
+    }
+}
@@ -0,0 +1,341 @@
+//! The comparison behind the default report, as a library API.
+//!
+//! A [`Comparer`] is configured with the two inputs, how lines are
+//! normalized and which strategy loads them, and produces a
+//! [`ComparisonResult`] that any [`Renderer`](crate::render::Renderer) can
+//! print:
+//!
+//! ```no_run
+//! use file_compare::{Comparer, Renderer, TextRenderer};
+//!
+//! let result = Comparer::new("old.txt", "new.txt")
+//!     .ignore_case(true)
+//!     .moves(true)
+//!     .compare()?;
+//! println!("{} lines only in old.txt", result.only_in_first.len());
+//! TextRenderer.render(&result, &mut std::io::stdout())?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use crate::backend::{BTreeMapTable, HashMapTable, LineTable};
+use crate::clap_parser::Backend;
+use crate::external::{Record, compare_files};
+use crate::fingerprint::{FingerprintTable, compare_fingerprints};
+use crate::fuzzy::{ChangedLine, pair_near_matches};
+use crate::keys_missing_from;
+use crate::moves::{MovedLine, find_moved_lines};
+use crate::parallel::{build_symbol_tables, build_table};
+use crate::read_symbol_table;
+use crate::snapshot::{is_snapshot, load_snapshot};
+use crate::summary::Summary;
+use std::io::{self, BufRead};
+use tries::{PatriciaTrie, TernarySearchTrie};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+/// How the lines of both files are loaded and matched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    /// Both files in symbol tables of the chosen backend
+    InMemory,
+    /// Sorted runs spilled to disk, using about `memory_limit` bytes
+    External { memory_limit: usize },
+    /// Tables keyed by line fingerprints, optionally verified by re-reading
+    Hashed { verify: bool },
+}
+
+/// Builds and runs one comparison of two files.
+#[derive(Clone, Debug)]
+pub struct Comparer {
+    first: String,
+    second: String,
+    ignore_case: bool,
+    backend: Backend,
+    threads: usize,
+    strategy: Strategy,
+    moves: bool,
+    fuzzy: Option<usize>,
+}
+
+/// What a comparison found. Line numbers are zero-based, and each list is
+/// sorted by them.
+#[derive(Clone, Debug)]
+pub struct ComparisonResult {
+    pub first: String,
+    pub second: String,
+    /// Distinct lines of the first file absent from the second
+    pub only_in_first: Vec<(u32, String)>,
+    /// Distinct lines of the second file absent from the first
+    pub only_in_second: Vec<(u32, String)>,
+    /// Common lines at different relative positions, when requested
+    pub moved: Option<Vec<MovedLine>>,
+    /// Lines paired with a near match in the other file, when requested;
+    /// these are no longer listed as only in either file
+    pub changed: Option<Vec<ChangedLine>>,
+    /// Distinct non-empty lines of the first file
+    pub first_distinct: usize,
+    /// Distinct non-empty lines of the second file
+    pub second_distinct: usize,
+    /// Non-empty lines of the first file, counting repeats
+    pub first_total: usize,
+    /// Non-empty lines of the second file, counting repeats
+    pub second_total: usize,
+}
+
+impl ComparisonResult {
+    /// Distinct lines found in both files.
+    pub fn common_count(&self) -> usize {
+        let changed = self.changed.as_ref().map_or(0, Vec::len);
+        self.first_distinct - self.only_in_first.len() - changed
+    }
+
+    /// Line counts and similarity of the two files.
+    pub fn summary(&self) -> Summary {
+        Summary {
+            first_total: self.first_total,
+            first_distinct: self.first_distinct,
+            second_total: self.second_total,
+            second_distinct: self.second_distinct,
+            common: self.common_count(),
+        }
+    }
+}
+
+/// A file loaded into a symbol table by [`Comparer::build_tables`].
+pub struct LoadedTable<T> {
+    pub symbol_table: T,
+    /// Non-empty lines read, counting repeats
+    pub total_lines: usize,
+}
+
+impl Comparer {
+    /// Compares `first` against `second` line by line, case-sensitively, in
+    /// memory on the ternary trie.
+    pub fn new(first: impl Into<String>, second: impl Into<String>) -> Self {
+        Comparer {
+            first: first.into(),
+            second: second.into(),
+            ignore_case: false,
+            backend: Backend::Ternary,
+            threads: std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+            strategy: Strategy::InMemory,
+            moves: false,
+            fuzzy: None,
+        }
+    }
+
+    /// Compares lines case-insensitively, reporting them upper-cased.
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// The symbol table used by [`Strategy::InMemory`].
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Threads for loading the files in memory; defaults to the number of
+    /// CPUs.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Also finds moved lines; only [`Strategy::InMemory`] keeps the tables
+    /// this needs.
+    pub fn moves(mut self, moves: bool) -> Self {
+        self.moves = moves;
+        self
+    }
+
+    /// Pairs lines only in one file with a counterpart within
+    /// `max_distance` edits.
+    pub fn fuzzy(mut self, max_distance: Option<usize>) -> Self {
+        self.fuzzy = max_distance;
+        self
+    }
+
+    pub fn first(&self) -> &str {
+        &self.first
+    }
+
+    pub fn second(&self) -> &str {
+        &self.second
+    }
+
+    /// Runs the comparison with the configured strategy.
+    pub fn compare(&self) -> io::Result<ComparisonResult> {
+        if self.moves && self.strategy != Strategy::InMemory {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "moved lines can only be found comparing in memory",
+            ));
+        }
+        if self.strategy != Strategy::InMemory && is_snapshot(&self.second) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "snapshots can only be compared in memory",
+            ));
+        }
+
+        match self.strategy {
+            Strategy::InMemory => match self.backend {
+                Backend::Ternary => self.compare_in_memory::<TernarySearchTrie<u32>>(),
+                Backend::Patricia => self.compare_in_memory::<PatriciaTrie<u32>>(),
+                Backend::HashMap => self.compare_in_memory::<HashMapTable<u32>>(),
+                Backend::BTreeMap => self.compare_in_memory::<BTreeMapTable<u32>>(),
+            },
+            Strategy::External { memory_limit } => self.compare_external(memory_limit),
+            Strategy::Hashed { verify } => self.compare_hashed(verify),
+        }
+    }
+
+    /// Loads both files into tables of type `T`. The second may be a
+    /// snapshot made with the same `ignore_case`. An input that cannot be
+    /// read is an error naming it.
+    pub fn build_tables<T: LineTable>(&self) -> io::Result<(LoadedTable<T>, LoadedTable<T>)> {
+        let (first, second) = if is_snapshot(&self.second) {
+            let second = load_snapshot(&self.second, self.ignore_case);
+            (build_table(&self.first, self.ignore_case, self.threads), second)
+        } else {
+            build_symbol_tables(&self.first, &self.second, self.ignore_case, self.threads)
+        };
+        let (symbol_table_1, total_1) = first.map_err(|error| with_name(&self.first, error))?;
+        let (symbol_table_2, total_2) = second.map_err(|error| with_name(&self.second, error))?;
+        Ok((
+            LoadedTable {
+                symbol_table: symbol_table_1,
+                total_lines: total_1,
+            },
+            LoadedTable {
+                symbol_table: symbol_table_2,
+                total_lines: total_2,
+            },
+        ))
+    }
+
+    /// Loads `filename` into a table of type `T`, as
+    /// [`Comparer::build_tables`] loads the first file.
+    pub fn build_table<T: LineTable>(&self, filename: &str) -> io::Result<LoadedTable<T>> {
+        let (symbol_table, total_lines) = build_table(filename, self.ignore_case, self.threads)?;
+        Ok(LoadedTable {
+            symbol_table,
+            total_lines,
+        })
+    }
+
+    /// Loads the lines `reader` gives, such as an archive member's, into a
+    /// table of type `T`.
+    pub fn read_table<T: LineTable>(&self, reader: impl BufRead) -> io::Result<LoadedTable<T>> {
+        let mut symbol_table = T::default();
+        let total_lines = read_symbol_table(reader, &mut symbol_table, self.ignore_case)?;
+        Ok(LoadedTable {
+            symbol_table,
+            total_lines,
+        })
+    }
+
+    /// Compares tables built by [`Comparer::build_tables`].
+    pub fn compare_tables<T: LineTable>(
+        &self,
+        first: &LoadedTable<T>,
+        second: &LoadedTable<T>,
+    ) -> ComparisonResult {
+        let (symbol_table_1, symbol_table_2) = (&first.symbol_table, &second.symbol_table);
+        let mut only_in_first = keys_missing_from(symbol_table_1, symbol_table_2);
+        let mut only_in_second = keys_missing_from(symbol_table_2, symbol_table_1);
+        only_in_first.sort_by_key(|k| k.0);
+        only_in_second.sort_by_key(|k| k.0);
+
+        let moved = self
+            .moves
+            .then(|| find_moved_lines(symbol_table_1, symbol_table_2));
+
+        self.finish(
+            only_in_first,
+            only_in_second,
+            moved,
+            (symbol_table_1.get_size(), first.total_lines),
+            (symbol_table_2.get_size(), second.total_lines),
+        )
+    }
+
+    fn compare_in_memory<T: LineTable>(&self) -> io::Result<ComparisonResult> {
+        let (first, second) = self.build_tables::<T>()?;
+        Ok(self.compare_tables(&first, &second))
+    }
+
+    fn compare_external(&self, memory_limit: usize) -> io::Result<ComparisonResult> {
+        let mut comparison =
+            compare_files(&self.first, &self.second, self.ignore_case, memory_limit)?;
+        let only_in_first = (&mut comparison.first_only).collect::<io::Result<Vec<Record>>>()?;
+        let only_in_second = (&mut comparison.second_only).collect::<io::Result<Vec<Record>>>()?;
+        let summary = comparison.summary;
+
+        Ok(self.finish(
+            only_in_first,
+            only_in_second,
+            None,
+            (summary.first_distinct, summary.first_total),
+            (summary.second_distinct, summary.second_total),
+        ))
+    }
+
+    fn compare_hashed(&self, verify: bool) -> io::Result<ComparisonResult> {
+        let build = |filename: &str| {
+            FingerprintTable::build(filename, self.ignore_case, verify)
+                .map_err(|error| with_name(filename, error))
+        };
+        let mut first = build(&self.first)?;
+        let mut second = build(&self.second)?;
+        let (only_in_first, only_in_second) =
+            compare_fingerprints(&mut first, &mut second, verify)?;
+
+        Ok(self.finish(
+            only_in_first,
+            only_in_second,
+            None,
+            (first.locations.len(), first.total_lines),
+            (second.locations.len(), second.total_lines),
+        ))
+    }
+
+    fn finish(
+        &self,
+        mut only_in_first: Vec<(u32, String)>,
+        mut only_in_second: Vec<(u32, String)>,
+        moved: Option<Vec<MovedLine>>,
+        (first_distinct, first_total): (usize, usize),
+        (second_distinct, second_total): (usize, usize),
+    ) -> ComparisonResult {
+        let changed = self.fuzzy.map(|max_distance| {
+            pair_near_matches(&mut only_in_first, &mut only_in_second, max_distance)
+        });
+
+        ComparisonResult {
+            first: self.first.clone(),
+            second: self.second.clone(),
+            only_in_first,
+            only_in_second,
+            moved,
+            changed,
+            first_distinct,
+            second_distinct,
+            first_total,
+            second_total,
+        }
+    }
+}
+
+// `error` prefixed with the input it came from
+fn with_name(filename: &str, error: io::Error) -> io::Error {
+    io::Error::new(error.kind(), format!("{}: {}", filename, error))
+}
@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod tests {
+    use crate::clap_parser::Backend;
+    use crate::comparer::{Comparer, Strategy};
+    use crate::render::{HtmlRenderer, Renderer, TextRenderer};
+    use crate::snapshot::create_snapshot;
+    use crate::test_util::write_temp;
+
+    fn lines(lines: &[(u32, &str)]) -> Vec<(u32, String)> {
+        lines
+            .iter()
+            .map(|(num, text)| (*num, text.to_string()))
+            .collect()
+    }
+
+    fn render(renderer: &dyn Renderer, comparer: &Comparer) -> String {
+        let mut out = Vec::new();
+        renderer
+            .render(&comparer.compare().unwrap(), &mut out)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_compare() {
+        let first = write_temp("first", "alpha\nbeta\ngamma\ndelta\nalpha\n");
+        let second = write_temp("second", "delta\nALPHA\nepsilon\ngama\n");
+        let comparer = Comparer::new(first.as_str(), second.as_str());
+
+        let result = comparer.clone().compare().unwrap();
+        assert_eq!(
+            result.only_in_first,
+            lines(&[(1, "beta"), (2, "gamma"), (4, "alpha")])
+        );
+        assert_eq!(
+            result.only_in_second,
+            lines(&[(1, "ALPHA"), (2, "epsilon"), (3, "gama")])
+        );
+        assert_eq!((result.first_distinct, result.second_distinct), (4, 4));
+        assert_eq!((result.first_total, result.second_total), (5, 4));
+        assert_eq!(result.common_count(), 1);
+        assert_eq!(result.summary().jaccard(), 1.0 / 7.0);
+        assert!(result.moved.is_none() && result.changed.is_none());
+
+        let result = comparer
+            .clone()
+            .ignore_case(true)
+            .fuzzy(Some(1))
+            .compare()
+            .unwrap();
+        assert_eq!(result.only_in_first, lines(&[(1, "BETA")]));
+        assert_eq!(result.only_in_second, lines(&[(2, "EPSILON")]));
+        assert_eq!(result.changed.as_ref().unwrap().len(), 1);
+        assert_eq!(result.common_count(), 2);
+    }
+
+    #[test]
+    fn test_strategies_and_backends_agree() {
+        let first = write_temp("agree-first", "one\ntwo\n\nthree\nTwo\nfour\none\n");
+        let second = write_temp("agree-second", "six\nFOUR\nseven\ntwo\n\nONE\n");
+        let base = Comparer::new(first.as_str(), second.as_str());
+
+        for ignore_case in [false, true] {
+            let comparer = base.clone().ignore_case(ignore_case);
+            let expected = render(&TextRenderer, &comparer);
+            let expected_html = render(&HtmlRenderer, &comparer);
+            let variants = [
+                comparer.clone().backend(Backend::Patricia),
+                comparer.clone().backend(Backend::HashMap),
+                comparer.clone().backend(Backend::BTreeMap).threads(4),
+                comparer
+                    .clone()
+                    .strategy(Strategy::External { memory_limit: 64 }),
+                comparer.clone().strategy(Strategy::Hashed { verify: true }),
+            ];
+            for variant in &variants {
+                assert_eq!(render(&TextRenderer, variant), expected, "{:?}", variant);
+                assert_eq!(render(&HtmlRenderer, variant), expected_html);
+            }
+        }
+    }
+
+    #[test]
+    fn test_backends_agree_on_blank_lines() {
+        let first = write_temp("blank-first", "\n\nsea\n\r\nshore\n\n");
+        let second = write_temp("blank-second", "sea\n\n\nsells\n");
+        let blank = write_temp("blank-only", "\n\r\n\n");
+
+        for (first, second) in [(&first, &second), (&blank, &second), (&blank, &first)] {
+            let comparer = Comparer::new(first.as_str(), second.as_str());
+            let expected = comparer
+                .clone()
+                .backend(Backend::Ternary)
+                .compare()
+                .unwrap();
+            for backend in [Backend::Patricia, Backend::HashMap, Backend::BTreeMap] {
+                let result = comparer.clone().backend(backend).compare().unwrap();
+                assert_eq!(
+                    result.only_in_first, expected.only_in_first,
+                    "{:?}",
+                    backend
+                );
+                assert_eq!(
+                    result.only_in_second, expected.only_in_second,
+                    "{:?}",
+                    backend
+                );
+                assert_eq!(result.summary(), expected.summary(), "{:?}", backend);
+            }
+        }
+        let result = Comparer::new(blank.as_str(), first.as_str())
+            .backend(Backend::HashMap)
+            .compare()
+            .unwrap();
+        assert_eq!(result.summary().first_distinct, 0);
+        assert_eq!(result.only_in_second, lines(&[(2, "sea"), (4, "shore")]));
+    }
+
+    #[test]
+    fn test_snapshot_as_second() {
+        let first = write_temp("snap-first", "one\ntwo\n\nthree\nTwo\nfour\none\n");
+        let second = write_temp("snap-second", "six\nFOUR\nseven\ntwo\n\nONE\nsix\n");
+        let snapshot = write_temp("snap-second.snap", "");
+        let (first, second, snapshot) = (first.as_str(), second.as_str(), snapshot.as_str());
+
+        for ignore_case in [false, true] {
+            create_snapshot(second, snapshot, ignore_case).unwrap();
+            let from_file = Comparer::new(first, second).ignore_case(ignore_case);
+            let from_snapshot = Comparer::new(first, snapshot).ignore_case(ignore_case);
+            let expected = render(&TextRenderer, &from_file);
+            assert_eq!(
+                render(&TextRenderer, &from_snapshot),
+                expected.replace(second, snapshot)
+            );
+
+            let mismatched = from_snapshot.clone().ignore_case(!ignore_case);
+            assert!(mismatched.compare().is_err());
+            let external = from_snapshot.strategy(Strategy::External { memory_limit: 64 });
+            assert!(external.compare().is_err());
+        }
+    }
+
+    #[test]
+    fn test_moves_need_memory() {
+        let comparer = Comparer::new("a", "b")
+            .moves(true)
+            .strategy(Strategy::Hashed { verify: false });
+        assert!(comparer.compare().is_err());
+    }
+
+    #[test]
+    fn test_missing_input() {
+        let second = write_temp("present", "alpha\n");
+        let second = second.as_str();
+        for strategy in [
+            Strategy::InMemory,
+            Strategy::External {
+                memory_limit: 1 << 20,
+            },
+            Strategy::Hashed { verify: false },
+        ] {
+            for (first, second) in [("missing", second), (second, "missing")] {
+                let comparer = Comparer::new(first, second).strategy(strategy);
+                let error = comparer.compare().err().unwrap();
+                assert_eq!(error.kind(), std::io::ErrorKind::NotFound, "{:?}", strategy);
+            }
+        }
+    }
+}
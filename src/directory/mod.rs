@@ -0,0 +1,323 @@
+//! `dir`: comparing two directories, first by which files they hold and then
+//! by the lines of the files in both.
+//!
+//! Each side's relative paths, always written with `/`, go into a Patricia
+//! trie: paths in one folder share the folder as a prefix, which the trie
+//! stores once, and walking it in key order lists the paths sorted, ready to
+//! be reported folder by folder.
+//!
+//! Either side may also be a tar or zip archive, read in memory rather than
+//! extracted to disk. A zip archive's members are read by path as they are
+//! compared; a tarball, which has no index to find a member by, is read once
+//! from start to end, loading every listed member into a table on the way.
+
+use crate::archive::{ArchiveKind, ZipMembers, for_each_tar_member, is_archive, member_names};
+use crate::backend::LineTable;
+use crate::comparer::{Comparer, LoadedTable};
+use crate::patterns::glob_to_regex;
+use crate::summary::Summary;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufReader};
+use std::path::Path;
+use tries::{PatriciaTrie, SymbolTable};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+/// Which files of a directory take part, by glob over their relative path.
+/// A glob without `/` is matched against the file or folder name alone, at
+/// any depth.
+pub struct PathFilter {
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+}
+
+struct Glob {
+    regex: Regex,
+    // Matched against the whole relative path rather than the name
+    whole_path: bool,
+}
+
+fn compile(globs: &[String]) -> Result<Vec<Glob>, regex::Error> {
+    globs
+        .iter()
+        .map(|glob| {
+            Ok(Glob {
+                regex: Regex::new(&glob_to_regex(glob))?,
+                whole_path: glob.contains('/'),
+            })
+        })
+        .collect()
+}
+
+fn matches(globs: &[Glob], relative: &str) -> bool {
+    let name = relative.rsplit('/').next().unwrap_or(relative);
+    globs.iter().any(|glob| {
+        glob.regex
+            .is_match(if glob.whole_path { relative } else { name })
+    })
+}
+
+impl PathFilter {
+    /// Files must match one of `include`, if any are given, and none of
+    /// `exclude`; folders matching `exclude` are not entered.
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, regex::Error> {
+        Ok(PathFilter {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+        })
+    }
+
+    pub fn excludes(&self, relative: &str) -> bool {
+        matches(&self.exclude, relative)
+    }
+
+    pub fn accepts(&self, relative: &str) -> bool {
+        !self.excludes(relative) && (self.include.is_empty() || matches(&self.include, relative))
+    }
+}
+
+// Whether `list_files` would list the member at `path` were its archive
+// extracted
+fn lists_member(path: &str, recursive: bool, filter: &PathFilter) -> bool {
+    let mut folders = path.match_indices('/').map(|(slash, _)| &path[..slash]);
+    let listed = if recursive {
+        !folders.any(|folder| filter.excludes(folder))
+    } else {
+        folders.next().is_none()
+    };
+    listed && filter.accepts(path)
+}
+
+/// The relative paths of the files under `root`, a directory or an archive,
+/// descending into folders only when `recursive`. Symbolic links to files are
+/// followed, links to folders are not, and names that are not UTF-8 are
+/// skipped.
+pub fn list_files(
+    root: &str,
+    recursive: bool,
+    filter: &PathFilter,
+) -> io::Result<PatriciaTrie<()>> {
+    let mut files = PatriciaTrie::new();
+    if is_archive(root) {
+        for member in member_names(root)? {
+            if lists_member(&member, recursive, filter) {
+                files.put(member, ());
+            }
+        }
+        return Ok(files);
+    }
+    let mut pending = vec![String::new()];
+    while let Some(folder) = pending.pop() {
+        for entry in fs::read_dir(Path::new(root).join(&folder))? {
+            let entry = entry?;
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            let relative = if folder.is_empty() {
+                name
+            } else {
+                format!("{}/{}", folder, name)
+            };
+            if entry.file_type()?.is_dir() {
+                if recursive && !filter.excludes(&relative) {
+                    pending.push(relative);
+                }
+            } else if entry.path().is_file() && filter.accepts(&relative) {
+                files.put(relative, ());
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// The line-set comparison of one file found in both directories.
+pub struct FileComparison {
+    pub path: String,
+    pub summary: Summary,
+}
+
+impl FileComparison {
+    /// Distinct lines of the first file missing from the second.
+    pub fn only_in_first(&self) -> usize {
+        self.summary.first_distinct - self.summary.common
+    }
+
+    /// Distinct lines of the second file missing from the first.
+    pub fn only_in_second(&self) -> usize {
+        self.summary.second_distinct - self.summary.common
+    }
+
+    /// Whether both files hold the same distinct lines.
+    pub fn same_lines(&self) -> bool {
+        self.only_in_first() == 0 && self.only_in_second() == 0
+    }
+}
+
+pub struct DirectoryComparison {
+    pub first: String,
+    pub second: String,
+    /// Relative paths, in key order
+    pub only_in_first: Vec<String>,
+    pub only_in_second: Vec<String>,
+    pub in_both: Vec<FileComparison>,
+}
+
+impl DirectoryComparison {
+    /// Files in both directories whose lines differ.
+    pub fn differing(&self) -> usize {
+        self.in_both
+            .iter()
+            .filter(|file| !file.same_lines())
+            .count()
+    }
+}
+
+// The keys of `files` missing from `other`, in key order
+fn paths_missing_from(files: &PatriciaTrie<()>, other: &PatriciaTrie<()>) -> Vec<String> {
+    let mut missing = Vec::new();
+    files.for_each_entry(&mut |path, _| {
+        if !other.contains(path) {
+            missing.push(path.to_string());
+        }
+        true
+    });
+    missing
+}
+
+// How the file at `relative` under `root` is named: a path on disk, or an
+// `ARCHIVE:PATH` member
+fn file_path(root: &str, relative: &str) -> String {
+    if is_archive(root) {
+        format!("{}:{}", root, relative)
+    } else {
+        Path::new(root)
+            .join(relative)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+// One of the directories or archives compared
+enum Side<T> {
+    Folder,
+    Zip(ZipMembers),
+    // The table of every listed member, keyed by path
+    Tar(HashMap<String, LoadedTable<T>>),
+}
+
+impl<T: LineTable> Side<T> {
+    // Opens `root` and lists its files; `comparer` sets how a tarball's
+    // members are loaded
+    fn open(
+        root: &str,
+        recursive: bool,
+        filter: &PathFilter,
+        comparer: &Comparer,
+    ) -> io::Result<(Self, PatriciaTrie<()>)> {
+        match ArchiveKind::of(root) {
+            Some(ArchiveKind::Tar | ArchiveKind::TarGz) if is_archive(root) => {
+                let mut files = PatriciaTrie::new();
+                let mut tables = HashMap::new();
+                // A path held more than once keeps its last copy
+                for_each_tar_member(root, |path, reader| {
+                    if lists_member(path, recursive, filter) {
+                        let table = comparer.read_table(BufReader::new(reader))?;
+                        tables.insert(path.to_string(), table);
+                        files.put(path.to_string(), ());
+                    }
+                    Ok(())
+                })?;
+                Ok((Side::Tar(tables), files))
+            }
+            Some(ArchiveKind::Zip) if is_archive(root) => {
+                let zip = ZipMembers::open(root)?;
+                let mut files = PatriciaTrie::new();
+                for path in zip.names() {
+                    if lists_member(path, recursive, filter) {
+                        files.put(path.to_string(), ());
+                    }
+                }
+                Ok((Side::Zip(zip), files))
+            }
+            _ => Ok((Side::Folder, list_files(root, recursive, filter)?)),
+        }
+    }
+
+    // The table of the listed file at `relative`, named `name` as `file_path`
+    // names it
+    fn table(
+        &mut self,
+        relative: &str,
+        name: &str,
+        comparer: &Comparer,
+    ) -> io::Result<LoadedTable<T>> {
+        match self {
+            Side::Folder => comparer.build_table(name),
+            Side::Zip(zip) => comparer.read_table(BufReader::new(zip.by_name(relative)?)),
+            // Each path is compared once, so its table can be handed over
+            Side::Tar(tables) => Ok(tables
+                .remove(relative)
+                .expect("every listed member is loaded")),
+        }
+    }
+}
+
+fn with_path(path: &str, error: io::Error) -> io::Error {
+    io::Error::new(error.kind(), format!("{}: {}", path, error))
+}
+
+/// Compares the files under `first` and `second`, loading their lines into
+/// tables of type `T`. `comparer` sets up the comparison of two files' lines.
+pub fn compare_directories<T: LineTable>(
+    first: &str,
+    second: &str,
+    recursive: bool,
+    filter: &PathFilter,
+    comparer: &dyn Fn(String, String) -> Comparer,
+) -> io::Result<DirectoryComparison> {
+    let loader = comparer(first.to_string(), second.to_string());
+    let open = |root| Side::<T>::open(root, recursive, filter, &loader);
+    let (mut first_side, first_files) = open(first).map_err(|error| with_path(first, error))?;
+    let (mut second_side, second_files) = open(second).map_err(|error| with_path(second, error))?;
+
+    let mut in_both = Vec::new();
+    let mut result = Ok(());
+    first_files.for_each_entry(&mut |path, _| {
+        if !second_files.contains(path) {
+            return true;
+        }
+        let comparer = comparer(file_path(first, path), file_path(second, path));
+        let (first_file, second_file) = (comparer.first(), comparer.second());
+        let tables = first_side
+            .table(path, first_file, &comparer)
+            .map_err(|error| with_path(first_file, error))
+            .and_then(|first_table| {
+                let second_table = second_side
+                    .table(path, second_file, &comparer)
+                    .map_err(|error| with_path(second_file, error))?;
+                Ok((first_table, second_table))
+            });
+        match tables {
+            Ok((first_table, second_table)) => in_both.push(FileComparison {
+                path: path.to_string(),
+                summary: comparer
+                    .compare_tables(&first_table, &second_table)
+                    .summary(),
+            }),
+            Err(error) => result = Err(error),
+        }
+        result.is_ok()
+    });
+    result?;
+
+    Ok(DirectoryComparison {
+        first: first.to_string(),
+        second: second.to_string(),
+        only_in_first: paths_missing_from(&first_files, &second_files),
+        only_in_second: paths_missing_from(&second_files, &first_files),
+        in_both,
+    })
+}
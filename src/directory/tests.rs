@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod tests {
+    use crate::comparer::Comparer;
+    use crate::directory::{PathFilter, compare_directories, list_files};
+    use crate::test_util::{TempPath, temp_path};
+    use tries::{PrefixSearch, SymbolTable, TernarySearchTrie};
+
+    // A fresh directory holding `files`, given by relative path and contents
+    fn temp_directory(name: &str, files: &[(&str, &str)]) -> TempPath {
+        let root = temp_path(name);
+        for (path, contents) in files {
+            let path = root.as_ref().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        root
+    }
+
+    fn filter(include: &[&str], exclude: &[&str]) -> PathFilter {
+        let strings = |globs: &[&str]| {
+            globs
+                .iter()
+                .map(|glob| glob.to_string())
+                .collect::<Vec<_>>()
+        };
+        PathFilter::new(&strings(include), &strings(exclude)).unwrap()
+    }
+
+    #[test]
+    fn test_path_filter() {
+        let filter = filter(&["*.csv", "logs/**"], &["tmp", "**/old-*"]);
+        assert!(filter.accepts("a.csv"));
+        assert!(filter.accepts("nested/deeper/a.csv"));
+        assert!(filter.accepts("logs/2024/app.log"));
+        assert!(!filter.accepts("notes.txt"));
+        assert!(!filter.accepts("data/old-a.csv"));
+        assert!(filter.excludes("nested/tmp"));
+        assert!(!filter.excludes("nested/tmp2"));
+    }
+
+    #[test]
+    fn test_list_files() {
+        let root = temp_directory(
+            "list",
+            &[
+                ("a.txt", "a\n"),
+                ("sub/b.txt", "b\n"),
+                ("sub/deep/c.txt", "c\n"),
+                ("tmp/d.txt", "d\n"),
+            ],
+        );
+
+        let top = list_files(root.as_str(), false, &filter(&[], &[])).unwrap();
+        assert_eq!(top.get_all_keys(), vec!["a.txt"]);
+
+        let all = list_files(root.as_str(), true, &filter(&[], &["tmp"])).unwrap();
+        assert_eq!(
+            all.get_all_keys(),
+            vec!["a.txt", "sub/b.txt", "sub/deep/c.txt"]
+        );
+        assert_eq!(
+            all.get_keys_with_prefix("sub/"),
+            vec!["sub/b.txt", "sub/deep/c.txt"]
+        );
+    }
+
+    #[test]
+    fn test_compare_directories() {
+        let first = temp_directory(
+            "first",
+            &[
+                ("same.txt", "x\ny\n"),
+                ("gone.txt", "g\n"),
+                ("sub/changed.txt", "keep\nold\nOther\n"),
+                ("sub/skip.bin", "binary\n"),
+            ],
+        );
+        let second = temp_directory(
+            "second",
+            &[
+                ("same.txt", "y\nx\nx\n"),
+                ("sub/changed.txt", "keep\nother\nnew\n"),
+                ("sub/added.txt", "a\n"),
+            ],
+        );
+
+        let comparer = |first: String, second: String| Comparer::new(first, second);
+        let comparison = compare_directories::<TernarySearchTrie<u32>>(
+            first.as_str(),
+            second.as_str(),
+            true,
+            &filter(&["*.txt"], &[]),
+            &comparer,
+        )
+        .unwrap();
+        assert_eq!(comparison.only_in_first, vec!["gone.txt"]);
+        assert_eq!(comparison.only_in_second, vec!["sub/added.txt"]);
+        let paths: Vec<&str> = comparison
+            .in_both
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["same.txt", "sub/changed.txt"]);
+        assert!(comparison.in_both[0].same_lines());
+        let changed = &comparison.in_both[1];
+        assert_eq!((changed.only_in_first(), changed.only_in_second()), (2, 2));
+        assert_eq!(changed.summary.common, 1);
+        assert_eq!(comparison.differing(), 1);
+
+        let comparer =
+            |first: String, second: String| Comparer::new(first, second).ignore_case(true);
+        let comparison = compare_directories::<TernarySearchTrie<u32>>(
+            first.as_str(),
+            second.as_str(),
+            false,
+            &filter(&[], &[]),
+            &comparer,
+        )
+        .unwrap();
+        assert_eq!(comparison.in_both.len(), 1);
+        assert_eq!(comparison.differing(), 0);
+
+        assert!(
+            compare_directories::<TernarySearchTrie<u32>>(
+                first.as_str(),
+                "missing",
+                false,
+                &filter(&[], &[]),
+                &comparer
+            )
+            .is_err()
+        );
+    }
+}
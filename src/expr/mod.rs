@@ -0,0 +1,300 @@
+//! The set-algebra language of the `expr` subcommand.
+//!
+//! Operands are file names, each standing for the set of its lines. From
+//! loosest to tightest binding, the operators are `|` (union), `^` (symmetric
+//! difference), `&` (intersection) and `-` (difference), all left-associative,
+//! as for Python sets; parentheses group. So `(a.txt | b.txt) - c.txt & d.txt`
+//! reads as `((a.txt | b.txt) - c.txt) & d.txt`.
+//!
+//! A file name runs until whitespace, a parenthesis or one of `|&^`. A `-` only
+//! acts as an operator at the start of a word, so `file-compare.txt` is one
+//! name; names with spaces can be double-quoted.
+
+use crate::line_source::LineSource;
+use std::fmt;
+use std::io;
+use tries::{SymbolTable, TernarySearchTrie};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    Union,
+    Intersection,
+    Difference,
+    SymmetricDifference,
+}
+
+impl Operator {
+    fn from_char(c: char) -> Option<Operator> {
+        match c {
+            '|' => Some(Operator::Union),
+            '&' => Some(Operator::Intersection),
+            '-' => Some(Operator::Difference),
+            '^' => Some(Operator::SymmetricDifference),
+            _ => None,
+        }
+    }
+
+    fn precedence(self) -> u8 {
+        match self {
+            Operator::Union => 1,
+            Operator::SymmetricDifference => 2,
+            Operator::Intersection => 3,
+            Operator::Difference => 4,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    File(String),
+    Binary(Box<Expr>, Operator, Box<Expr>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    File(String),
+    Operator(Operator),
+    Open,
+    Close,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError(String);
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' {
+            chars.next();
+            tokens.push(Token::Open);
+        } else if c == ')' {
+            chars.next();
+            tokens.push(Token::Close);
+        } else if let Some(operator) = Operator::from_char(c) {
+            chars.next();
+            tokens.push(Token::Operator(operator));
+        } else if c == '"' {
+            chars.next();
+            let mut name = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(c) => name.push(c),
+                    None => return Err(ParseError("unterminated quoted file name".to_string())),
+                }
+            }
+            tokens.push(Token::File(name));
+        } else {
+            let mut name = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || matches!(c, '(' | ')' | '|' | '&' | '^') {
+                    break;
+                }
+                name.push(c);
+                chars.next();
+            }
+            tokens.push(Token::File(name));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Parses an expression, for use as a clap value parser.
+pub fn parse_expr(input: &str) -> Result<Expr, ParseError> {
+    let tokens = tokenize(input)?;
+    let mut pos = 0;
+    let expr = parse_binary(&tokens, &mut pos, 0)?;
+    match tokens.get(pos) {
+        None => Ok(expr),
+        Some(token) => Err(ParseError(format!("unexpected {:?}", token))),
+    }
+}
+
+// Precedence climbing: parses operands joined by operators binding tighter
+// than `min_precedence`
+fn parse_binary(tokens: &[Token], pos: &mut usize, min_precedence: u8) -> Result<Expr, ParseError> {
+    let mut left = parse_operand(tokens, pos)?;
+    while let Some(Token::Operator(operator)) = tokens.get(*pos) {
+        if operator.precedence() <= min_precedence {
+            break;
+        }
+        *pos += 1;
+        let right = parse_binary(tokens, pos, operator.precedence())?;
+        left = Expr::Binary(Box::new(left), *operator, Box::new(right));
+    }
+    Ok(left)
+}
+
+fn parse_operand(tokens: &[Token], pos: &mut usize) -> Result<Expr, ParseError> {
+    match tokens.get(*pos) {
+        Some(Token::File(name)) => {
+            *pos += 1;
+            Ok(Expr::File(name.clone()))
+        }
+        Some(Token::Open) => {
+            *pos += 1;
+            let expr = parse_binary(tokens, pos, 0)?;
+            if tokens.get(*pos) != Some(&Token::Close) {
+                return Err(ParseError("expected ')'".to_string()));
+            }
+            *pos += 1;
+            Ok(expr)
+        }
+        Some(token) => Err(ParseError(format!(
+            "expected a file name or '(', found {:?}",
+            token
+        ))),
+        None => Err(ParseError("expected a file name or '('".to_string())),
+    }
+}
+
+/// Where a line of the result came from: an index into
+/// [`Evaluator::files`] and the zero-based line number in that file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Source {
+    pub file: usize,
+    pub line: u32,
+}
+
+/// Evaluates expressions, building each named file's symbol table once no
+/// matter how often it appears.
+pub struct Evaluator {
+    pub files: Vec<String>,
+    // The table of each of `files`, by index
+    tables: Vec<TernarySearchTrie<Vec<Source>>>,
+    ignore_case: bool,
+}
+
+impl Evaluator {
+    pub fn new(ignore_case: bool) -> Self {
+        Evaluator {
+            files: Vec::new(),
+            tables: Vec::new(),
+            ignore_case,
+        }
+    }
+
+    /// Evaluates `expr` to a table from each resulting line to every place it
+    /// occurs among the operands that contributed it. Fails if an operand
+    /// cannot be read.
+    pub fn evaluate(&mut self, expr: &Expr) -> io::Result<TernarySearchTrie<Vec<Source>>> {
+        match expr {
+            Expr::File(name) => {
+                let file = self.load(name)?;
+                // A copy, as the table stays cached for later appearances
+                let mut sources = TernarySearchTrie::new();
+                self.tables[file].for_each_entry(&mut |key, file_sources| {
+                    sources.put(key.to_string(), file_sources.clone());
+                    true
+                });
+                Ok(sources)
+            }
+            Expr::Binary(left, operator, right) => {
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+                Ok(combine(&left, *operator, &right))
+            }
+        }
+    }
+
+    // The index of `name` in `files`, reading its table on first appearance
+    fn load(&mut self, name: &str) -> io::Result<usize> {
+        if let Some(file) = self.files.iter().position(|known| known == name) {
+            return Ok(file);
+        }
+        let file = self.files.len();
+        let mut sources = TernarySearchTrie::new();
+        LineSource::open(name)
+            .and_then(|source| {
+                source.for_each_line(|line, current_line| {
+                    let key = if self.ignore_case {
+                        current_line.to_uppercase()
+                    } else {
+                        current_line.to_string()
+                    };
+                    let line = line as u32;
+                    sources.put(key, vec![Source { file, line }]);
+                })
+            })
+            .map_err(|error| io::Error::new(error.kind(), format!("{}: {}", name, error)))?;
+        self.files.push(name.to_string());
+        self.tables.push(sources);
+        Ok(file)
+    }
+}
+
+fn combine(
+    left: &TernarySearchTrie<Vec<Source>>,
+    operator: Operator,
+    right: &TernarySearchTrie<Vec<Source>>,
+) -> TernarySearchTrie<Vec<Source>> {
+    let mut result = TernarySearchTrie::new();
+
+    for key in left.get_all_keys() {
+        let mut sources = left.get(&key).unwrap();
+        let in_right = right.get(&key);
+        let keep = match operator {
+            Operator::Union | Operator::Intersection => {
+                if let Some(right_sources) = &in_right {
+                    merge_sources(&mut sources, right_sources);
+                }
+                operator == Operator::Union || in_right.is_some()
+            }
+            Operator::Difference | Operator::SymmetricDifference => in_right.is_none(),
+        };
+        if keep {
+            result.put(key, sources);
+        }
+    }
+
+    if matches!(operator, Operator::Union | Operator::SymmetricDifference) {
+        for key in right.get_all_keys() {
+            if !left.contains(&key) {
+                result.put(key.clone(), right.get(&key).unwrap());
+            }
+        }
+    }
+
+    result
+}
+
+fn merge_sources(sources: &mut Vec<Source>, other: &[Source]) {
+    for source in other {
+        if !sources.contains(source) {
+            sources.push(*source);
+        }
+    }
+}
+
+/// Flattens an evaluated table into report rows, ordered by where each line
+/// first occurs: by file in order of appearance in the expression, then by
+/// line number.
+pub fn result_lines(result: &TernarySearchTrie<Vec<Source>>) -> Vec<(Vec<Source>, String)> {
+    let mut lines: Vec<(Vec<Source>, String)> = result
+        .get_all_keys()
+        .into_iter()
+        .map(|key| {
+            let mut sources = result.get(&key).unwrap();
+            sources.sort_by_key(|source| (source.file, source.line));
+            (sources, key)
+        })
+        .collect();
+    lines.sort_by_key(|(sources, _)| (sources[0].file, sources[0].line));
+    lines
+}
@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod tests {
+    use crate::expr::{Evaluator, Expr, Operator, Source, combine, parse_expr};
+    use crate::test_util::write_temp;
+    use std::io;
+    use tries::{SymbolTable, TernarySearchTrie};
+
+    fn file(name: &str) -> Box<Expr> {
+        Box::new(Expr::File(name.to_string()))
+    }
+
+    fn binary(left: Box<Expr>, operator: Operator, right: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr::Binary(left, operator, right))
+    }
+
+    fn set(file: usize, lines: &[&str]) -> TernarySearchTrie<Vec<Source>> {
+        let mut table = TernarySearchTrie::new();
+        for (line, key) in lines.iter().enumerate() {
+            table.put(
+                key.to_string(),
+                vec![Source {
+                    file,
+                    line: line as u32,
+                }],
+            );
+        }
+        table
+    }
+
+    #[test]
+    fn test_precedence() {
+        assert_eq!(
+            parse_expr("(a.txt | b.txt) - c.txt & d.txt"),
+            Ok(*binary(
+                binary(
+                    binary(file("a.txt"), Operator::Union, file("b.txt")),
+                    Operator::Difference,
+                    file("c.txt")
+                ),
+                Operator::Intersection,
+                file("d.txt")
+            ))
+        );
+        assert_eq!(
+            parse_expr("a | b ^ c & d"),
+            Ok(*binary(
+                file("a"),
+                Operator::Union,
+                binary(
+                    file("b"),
+                    Operator::SymmetricDifference,
+                    binary(file("c"), Operator::Intersection, file("d"))
+                )
+            ))
+        );
+        assert_eq!(
+            parse_expr("a - b - c"),
+            Ok(*binary(
+                binary(file("a"), Operator::Difference, file("b")),
+                Operator::Difference,
+                file("c")
+            ))
+        );
+    }
+
+    #[test]
+    fn test_file_names() {
+        assert_eq!(
+            parse_expr("file-compare.txt -other.txt"),
+            Ok(*binary(
+                file("file-compare.txt"),
+                Operator::Difference,
+                file("other.txt")
+            ))
+        );
+        assert_eq!(
+            parse_expr("\"my file.txt\"&b"),
+            Ok(*binary(
+                file("my file.txt"),
+                Operator::Intersection,
+                file("b")
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_expr("").is_err());
+        assert!(parse_expr("a |").is_err());
+        assert!(parse_expr("(a | b").is_err());
+        assert!(parse_expr("a b").is_err());
+        assert!(parse_expr("\"a").is_err());
+    }
+
+    #[test]
+    fn test_combine() {
+        let a = set(0, &["she", "sells", "sea"]);
+        let b = set(1, &["sea", "shells"]);
+
+        let union = combine(&a, Operator::Union, &b);
+        assert_eq!(union.get_all_keys(), ["sea", "sells", "she", "shells"]);
+        assert_eq!(
+            union.get("sea"),
+            Some(vec![
+                Source { file: 0, line: 2 },
+                Source { file: 1, line: 0 }
+            ])
+        );
+
+        let intersection = combine(&a, Operator::Intersection, &b);
+        assert_eq!(intersection.get_all_keys(), ["sea"]);
+        assert_eq!(intersection.get("sea").unwrap().len(), 2);
+
+        let difference = combine(&a, Operator::Difference, &b);
+        assert_eq!(difference.get_all_keys(), ["sells", "she"]);
+
+        let symmetric = combine(&a, Operator::SymmetricDifference, &b);
+        assert_eq!(symmetric.get_all_keys(), ["sells", "she", "shells"]);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let a_file = write_temp("a.txt", "she\nsells\nsea\n");
+        let b_file = write_temp("b.txt", "sea\nshells\n");
+        let (a, b) = (a_file.as_str(), b_file.as_str());
+        let mut evaluator = Evaluator::new(false);
+
+        // `a` appears twice but is one operand
+        let expr = binary(
+            binary(file(a), Operator::Union, file(b)),
+            Operator::Difference,
+            file(a),
+        );
+        let result = evaluator.evaluate(&expr).unwrap();
+        assert_eq!(result.get_all_keys(), ["shells"]);
+        assert_eq!(
+            result.get("shells"),
+            Some(vec![Source { file: 1, line: 1 }])
+        );
+        assert_eq!(evaluator.files, [a, b]);
+
+        let missing = format!("{}-missing", a);
+        let result = evaluator.evaluate(&binary(file(a), Operator::Union, file(&missing)));
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::NotFound);
+    }
+}
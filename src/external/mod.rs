@@ -0,0 +1,446 @@
+//! Bounded-memory comparison for `--memory-limit`, for files too large to
+//! hold in a trie.
+//!
+//! Each file is read in chunks that fit the limit; every chunk is sorted,
+//! deduplicated and spilled to a temporary run file. The runs are then merged
+//! back into one sorted, deduplicated stream per file, and the two streams are
+//! walked side by side like a merge join. The differences found are spilled
+//! and sorted again, by line number, so the report comes out exactly as the
+//! in-memory path prints it.
+//!
+//! Deduplication matches the trie's semantics: a repeated line keeps the line
+//! number of its last occurrence, and empty lines are skipped.
+
+use crate::line_source::LineSource;
+use crate::summary::Summary;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+// Rough heap cost of one buffered record beyond its text
+const RECORD_OVERHEAD: usize = 48;
+
+// Most runs merged at once; more are merged in several passes
+const MAX_MERGE_FAN_IN: usize = 64;
+
+/// A line number and the line's text (already normalized).
+pub type Record = (u32, String);
+
+/// Parses a size such as `512M`, `2G` or `65536` (bytes), for use as a clap
+/// value parser. Suffixes are binary: `K` is 1024 bytes.
+pub fn parse_size(input: &str) -> Result<usize, String> {
+    let input = input.trim();
+    let (digits, multiplier) = match input.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&input[..i], 1 << 10),
+        Some((i, 'm' | 'M')) => (&input[..i], 1 << 20),
+        Some((i, 'g' | 'G')) => (&input[..i], 1 << 30),
+        _ => (input, 1),
+    };
+    let value: usize = digits
+        .parse()
+        .map_err(|_| format!("invalid size '{}'", input))?;
+    if value == 0 {
+        return Err("size must be greater than zero".to_string());
+    }
+    value
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("size '{}' is too large", input))
+}
+
+/// A temporary directory for run files, removed with everything in it when
+/// dropped.
+pub struct SpillDir {
+    path: PathBuf,
+    next_run: AtomicUsize,
+}
+
+impl SpillDir {
+    pub fn new() -> io::Result<Self> {
+        static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "file_compare-{}-{}",
+            std::process::id(),
+            NEXT_DIR.fetch_add(1, AtomicOrdering::Relaxed)
+        ));
+        fs::create_dir_all(&path)?;
+        Ok(SpillDir {
+            path,
+            next_run: AtomicUsize::new(0),
+        })
+    }
+
+    fn next_run_path(&self) -> PathBuf {
+        let run = self.next_run.fetch_add(1, AtomicOrdering::Relaxed);
+        self.path.join(format!("run-{}", run))
+    }
+}
+
+impl Drop for SpillDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// The order records are sorted in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortOrder {
+    /// By text, with repeated texts collapsed to their last line number
+    ByText,
+    /// By line number
+    ByLine,
+}
+
+impl SortOrder {
+    fn compare(self, a: &Record, b: &Record) -> Ordering {
+        match self {
+            SortOrder::ByText => a.1.cmp(&b.1).then(a.0.cmp(&b.0)),
+            SortOrder::ByLine => a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)),
+        }
+    }
+}
+
+fn write_record<W: Write>(writer: &mut W, record: &Record) -> io::Result<()> {
+    writer.write_all(&record.0.to_le_bytes())?;
+    writer.write_all(&(record.1.len() as u32).to_le_bytes())?;
+    writer.write_all(record.1.as_bytes())
+}
+
+fn read_record<R: Read>(reader: &mut R) -> io::Result<Option<Record>> {
+    let mut header = [0u8; 8];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error),
+    }
+    let line = u32::from_le_bytes(header[..4].try_into().unwrap());
+    let len = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+    let mut text = vec![0u8; len];
+    reader.read_exact(&mut text)?;
+    let text =
+        String::from_utf8(text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(Some((line, text)))
+}
+
+/// Reads the records of one run file in order.
+struct RunReader {
+    reader: BufReader<File>,
+}
+
+impl RunReader {
+    fn open(path: &Path) -> io::Result<Self> {
+        Ok(RunReader {
+            reader: BufReader::new(File::open(path)?),
+        })
+    }
+}
+
+impl Iterator for RunReader {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<io::Result<Record>> {
+        read_record(&mut self.reader).transpose()
+    }
+}
+
+/// Sorts records of any volume by buffering up to a memory budget and
+/// spilling sorted runs.
+pub struct ExternalSorter<'a> {
+    dir: &'a SpillDir,
+    order: SortOrder,
+    budget: usize,
+    buffer: Vec<Record>,
+    used: usize,
+    runs: Vec<PathBuf>,
+}
+
+impl<'a> ExternalSorter<'a> {
+    pub fn new(dir: &'a SpillDir, order: SortOrder, budget: usize) -> Self {
+        ExternalSorter {
+            dir,
+            order,
+            budget,
+            buffer: Vec::new(),
+            used: 0,
+            runs: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, record: Record) -> io::Result<()> {
+        self.used += record.1.len() + RECORD_OVERHEAD;
+        self.buffer.push(record);
+        if self.used >= self.budget {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        let order = self.order;
+        self.buffer.sort_by(|a, b| order.compare(a, b));
+        if order == SortOrder::ByText {
+            // Sorted by text then line, so the last of each run of equal texts
+            // carries the highest line number
+            self.buffer.reverse();
+            self.buffer.dedup_by(|later, earlier| later.1 == earlier.1);
+            self.buffer.reverse();
+        }
+
+        let path = self.dir.next_run_path();
+        let mut writer = BufWriter::new(File::create(&path)?);
+        for record in &self.buffer {
+            write_record(&mut writer, record)?;
+        }
+        writer.flush()?;
+
+        self.runs.push(path);
+        self.buffer.clear();
+        self.used = 0;
+        Ok(())
+    }
+
+    /// Spills what is left and merges every run into one sorted stream.
+    pub fn finish(mut self) -> io::Result<SortedRecords> {
+        if !self.buffer.is_empty() || self.runs.is_empty() {
+            self.spill()?;
+        }
+
+        // Merge in passes until few enough runs remain to open at once
+        while self.runs.len() > MAX_MERGE_FAN_IN {
+            let mut merged_runs = Vec::new();
+            for group in self.runs.chunks(MAX_MERGE_FAN_IN) {
+                let path = self.dir.next_run_path();
+                let mut writer = BufWriter::new(File::create(&path)?);
+                for record in SortedRecords::open(group, self.order)? {
+                    write_record(&mut writer, &record?)?;
+                }
+                writer.flush()?;
+                for run in group {
+                    fs::remove_file(run)?;
+                }
+                merged_runs.push(path);
+            }
+            self.runs = merged_runs;
+        }
+
+        SortedRecords::open(&self.runs, self.order)
+    }
+}
+
+// A run's next record, ordered for a min-heap on the sort order
+struct HeapEntry {
+    record: Record,
+    run: usize,
+    order: SortOrder,
+}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HeapEntry {}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.order
+            .compare(&self.record, &other.record)
+            .then(self.run.cmp(&other.run))
+    }
+}
+
+/// The k-way merge of sorted runs. In [`SortOrder::ByText`] order, records
+/// with equal text from different runs are collapsed as within a run.
+pub struct SortedRecords {
+    runs: Vec<RunReader>,
+    heap: BinaryHeap<Reverse<HeapEntry>>,
+    order: SortOrder,
+}
+
+impl SortedRecords {
+    fn open(paths: &[PathBuf], order: SortOrder) -> io::Result<Self> {
+        let mut merged = SortedRecords {
+            runs: Vec::with_capacity(paths.len()),
+            heap: BinaryHeap::new(),
+            order,
+        };
+        for (run, path) in paths.iter().enumerate() {
+            merged.runs.push(RunReader::open(path)?);
+            merged.refill(run)?;
+        }
+        Ok(merged)
+    }
+
+    fn refill(&mut self, run: usize) -> io::Result<()> {
+        if let Some(record) = self.runs[run].next().transpose()? {
+            self.heap.push(Reverse(HeapEntry {
+                record,
+                run,
+                order: self.order,
+            }));
+        }
+        Ok(())
+    }
+
+    fn pop(&mut self) -> io::Result<Option<Record>> {
+        let Some(Reverse(entry)) = self.heap.pop() else {
+            return Ok(None);
+        };
+        self.refill(entry.run)?;
+        Ok(Some(entry.record))
+    }
+
+    fn next_record(&mut self) -> io::Result<Option<Record>> {
+        let Some(mut record) = self.pop()? else {
+            return Ok(None);
+        };
+        if self.order == SortOrder::ByText {
+            while self
+                .heap
+                .peek()
+                .is_some_and(|Reverse(next)| next.record.1 == record.1)
+            {
+                let duplicate = self.pop()?.unwrap();
+                record.0 = record.0.max(duplicate.0);
+            }
+        }
+        Ok(Some(record))
+    }
+}
+
+impl Iterator for SortedRecords {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<io::Result<Record>> {
+        self.next_record().transpose()
+    }
+}
+
+/// Sorts the lines of `filename`, or of standard input for `-`, by text in
+/// bounded memory, normalizing them as `build_symbol_table` does. Also
+/// returns the number of non-empty lines.
+pub fn sort_file(
+    dir: &SpillDir,
+    filename: &str,
+    ignore_case: bool,
+    budget: usize,
+) -> io::Result<(SortedRecords, usize)> {
+    let mut sorter = ExternalSorter::new(dir, SortOrder::ByText, budget);
+    let mut total_lines = 0;
+    let mut result = Ok(());
+    LineSource::open(filename)
+        .and_then(|source| {
+            source.for_each_line(|index, current_line| {
+                let key = if ignore_case {
+                    current_line.to_uppercase()
+                } else {
+                    current_line.to_string()
+                };
+                if result.is_ok() && !key.is_empty() {
+                    result = sorter.push((index as u32, key));
+                    total_lines += 1;
+                }
+            })
+        })
+        .map_err(|error| io::Error::new(error.kind(), format!("{}: {}", filename, error)))?;
+    result?;
+    Ok((sorter.finish()?, total_lines))
+}
+
+/// Walks two text-sorted streams together, returning the records only in the
+/// first and only in the second, each sorted by line number, and the number
+/// of texts in both.
+pub fn merge_compare<I, J>(
+    dir: &SpillDir,
+    first: I,
+    second: J,
+    budget: usize,
+) -> io::Result<(SortedRecords, SortedRecords, usize)>
+where
+    I: Iterator<Item = io::Result<Record>>,
+    J: Iterator<Item = io::Result<Record>>,
+{
+    let mut first: Peekable<I> = first.peekable();
+    let mut second: Peekable<J> = second.peekable();
+    let mut first_only = ExternalSorter::new(dir, SortOrder::ByLine, budget / 2);
+    let mut second_only = ExternalSorter::new(dir, SortOrder::ByLine, budget / 2);
+    let mut common = 0;
+
+    loop {
+        let ordering = match (first.peek(), second.peek()) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(Ok(a)), Some(Ok(b))) => a.1.cmp(&b.1),
+            // Surface the error through the `?` below
+            (Some(Err(_)), _) => Ordering::Less,
+            (_, Some(Err(_))) => Ordering::Greater,
+        };
+        match ordering {
+            Ordering::Less => first_only.push(first.next().unwrap()?)?,
+            Ordering::Greater => second_only.push(second.next().unwrap()?)?,
+            Ordering::Equal => {
+                first.next();
+                second.next();
+                common += 1;
+            }
+        }
+    }
+
+    Ok((first_only.finish()?, second_only.finish()?, common))
+}
+
+/// Two files compared within a memory budget. Each side's differences stay
+/// on disk, sorted by line number, and are read back as they are consumed.
+pub struct DiskComparison {
+    pub summary: Summary,
+    pub first_only: SortedRecords,
+    pub second_only: SortedRecords,
+    // Holds the runs being read
+    _dir: SpillDir,
+}
+
+/// Compares `first` and `second` by sorting both on disk, using about
+/// `budget` bytes of memory.
+pub fn compare_files(
+    first: &str,
+    second: &str,
+    ignore_case: bool,
+    budget: usize,
+) -> io::Result<DiskComparison> {
+    let dir = SpillDir::new()?;
+    let (mut first_distinct, mut second_distinct) = (0, 0);
+
+    // One file is sorted at a time, so each may use the whole budget
+    let (sorted_1, first_total) = sort_file(&dir, first, ignore_case, budget)?;
+    let (sorted_2, second_total) = sort_file(&dir, second, ignore_case, budget)?;
+    let sorted_1 = sorted_1.inspect(|_| first_distinct += 1);
+    let sorted_2 = sorted_2.inspect(|_| second_distinct += 1);
+    let (first_only, second_only, common) = merge_compare(&dir, sorted_1, sorted_2, budget)?;
+
+    Ok(DiskComparison {
+        summary: Summary {
+            first_total,
+            first_distinct,
+            second_total,
+            second_distinct,
+            common,
+        },
+        first_only,
+        second_only,
+        _dir: dir,
+    })
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use crate::external::{
+        ExternalSorter, Record, SortOrder, SpillDir, merge_compare, parse_size, sort_file,
+    };
+    use crate::{build_symbol_table, keys_missing_from};
+    use std::io;
+    use tries::{SymbolTable, TernarySearchTrie};
+
+    // Small enough that every few records spill a new run
+    const TINY_BUDGET: usize = 128;
+
+    fn sorted(dir: &SpillDir, lines: &[&str], order: SortOrder) -> Vec<Record> {
+        let mut sorter = ExternalSorter::new(dir, order, TINY_BUDGET);
+        for (index, line) in lines.iter().enumerate() {
+            sorter.push((index as u32, line.to_string())).unwrap();
+        }
+        sorter
+            .finish()
+            .unwrap()
+            .collect::<io::Result<Vec<Record>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("65536"), Ok(65536));
+        assert_eq!(parse_size("4k"), Ok(4096));
+        assert_eq!(parse_size("512M"), Ok(512 << 20));
+        assert_eq!(parse_size("2G"), Ok(2 << 30));
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("12X").is_err());
+    }
+
+    #[test]
+    fn test_sort_by_text_keeps_last_occurrence() {
+        let dir = SpillDir::new().unwrap();
+        let lines = [
+            "pear", "apple", "fig", "apple", "kiwi", "pear", "date", "fig", "apple",
+        ];
+        let records = sorted(&dir, &lines, SortOrder::ByText);
+        assert_eq!(
+            records,
+            vec![
+                (8, "apple".to_string()),
+                (6, "date".to_string()),
+                (7, "fig".to_string()),
+                (4, "kiwi".to_string()),
+                (5, "pear".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_line() {
+        let dir = SpillDir::new().unwrap();
+        let mut sorter = ExternalSorter::new(&dir, SortOrder::ByLine, TINY_BUDGET);
+        for line in (0..200u32).rev() {
+            sorter.push((line, format!("line {}", line))).unwrap();
+        }
+        let lines: Vec<u32> = sorter.finish().unwrap().map(|r| r.unwrap().0).collect();
+        assert_eq!(lines, (0..200).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_many_runs_merge_in_passes() {
+        let dir = SpillDir::new().unwrap();
+        let lines: Vec<String> = (0..5000).map(|i| format!("key {}", i % 1500)).collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let records = sorted(&dir, &lines, SortOrder::ByText);
+        assert_eq!(records.len(), 1500);
+        assert!(records.windows(2).all(|pair| pair[0].1 < pair[1].1));
+        assert!(records.iter().all(|(line, _)| *line >= 3500));
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let dir = SpillDir::new().unwrap();
+        assert!(sorted(&dir, &[], SortOrder::ByText).is_empty());
+    }
+
+    #[test]
+    fn test_matches_in_memory_comparison() {
+        let dir = SpillDir::new().unwrap();
+        let first = dir.path.join("first.txt");
+        let second = dir.path.join("second.txt");
+        std::fs::write(&first, "one\ntwo\n\nthree\nTwo\nfour\none\nfive\nsix\n").unwrap();
+        std::fs::write(&second, "six\nFIVE\nseven\ntwo\n\neight\nONE\nnine\n").unwrap();
+        let (first, second) = (first.to_str().unwrap(), second.to_str().unwrap());
+
+        for ignore_case in [false, true] {
+            let mut table_1 = TernarySearchTrie::new();
+            let mut table_2 = TernarySearchTrie::new();
+            build_symbol_table(first, &mut table_1, ignore_case).unwrap();
+            build_symbol_table(second, &mut table_2, ignore_case).unwrap();
+            let mut expected_first = keys_missing_from(&table_1, &table_2);
+            let mut expected_second = keys_missing_from(&table_2, &table_1);
+            expected_first.sort_by_key(|k| k.0);
+            expected_second.sort_by_key(|k| k.0);
+
+            let (sorted_1, total_1) = sort_file(&dir, first, ignore_case, TINY_BUDGET).unwrap();
+            let (sorted_2, total_2) = sort_file(&dir, second, ignore_case, TINY_BUDGET).unwrap();
+            assert_eq!((total_1, total_2), (8, 7));
+            let (first_only, second_only, common) =
+                merge_compare(&dir, sorted_1, sorted_2, TINY_BUDGET).unwrap();
+            assert_eq!(common, table_1.get_size() - expected_first.len());
+            let first_only: Vec<Record> = first_only.map(Result::unwrap).collect();
+            let second_only: Vec<Record> = second_only.map(Result::unwrap).collect();
+
+            assert_eq!(first_only, expected_first);
+            assert_eq!(second_only, expected_second);
+        }
+    }
+
+    #[test]
+    fn test_spill_dir_removed_on_drop() {
+        let dir = SpillDir::new().unwrap();
+        let path = dir.path.clone();
+        sorted(&dir, &["b", "a"], SortOrder::ByText);
+        assert!(path.exists());
+        drop(dir);
+        assert!(!path.exists());
+    }
+}
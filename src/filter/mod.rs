@@ -0,0 +1,98 @@
+//! The `filter` subcommand: a `grep -Fx -f` replacement.
+//!
+//! Only the list is loaded into a trie. The filtered input is streamed a line
+//! at a time and written back out unchanged, in its original order, so its
+//! size does not matter. Input lines are matched with a trailing `\r` removed
+//! and bytes that are not UTF-8 decoded lossily, but kept lines are written
+//! byte for byte as read.
+
+use crate::build_symbol_table;
+use std::io::{self, BufRead, Write};
+use tries::{SymbolTable, TernarySearchTrie};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+/// Which part of an input line is looked up in the list.
+pub enum KeySelector {
+    Line,
+    /// A one-based field, as numbered by `cut`
+    Field {
+        index: usize,
+        delimiter: String,
+    },
+}
+
+impl KeySelector {
+    fn select<'a>(&self, line: &'a str) -> &'a str {
+        match self {
+            KeySelector::Line => line,
+            KeySelector::Field { index, delimiter } => {
+                line.split(delimiter.as_str()).nth(index - 1).unwrap_or("")
+            }
+        }
+    }
+}
+
+pub struct LineFilter {
+    list: TernarySearchTrie<u32>,
+    key: KeySelector,
+    ignore_case: bool,
+    exclude: bool,
+}
+
+impl LineFilter {
+    /// Loads `list` so lines whose key appears in it are kept, or with
+    /// `exclude`, dropped. A list that cannot be read is an error rather than
+    /// an empty list.
+    pub fn new(list: &str, key: KeySelector, ignore_case: bool, exclude: bool) -> io::Result<Self> {
+        let mut symbol_table = TernarySearchTrie::<u32>::new();
+        build_symbol_table(list, &mut symbol_table, ignore_case)?;
+        Ok(Self::from_table(symbol_table, key, ignore_case, exclude))
+    }
+
+    pub fn from_table(
+        list: TernarySearchTrie<u32>,
+        key: KeySelector,
+        ignore_case: bool,
+        exclude: bool,
+    ) -> Self {
+        LineFilter {
+            list,
+            key,
+            ignore_case,
+            exclude,
+        }
+    }
+
+    pub fn keeps(&self, line: &str) -> bool {
+        let key = self.key.select(line);
+        let listed = if self.ignore_case {
+            self.list.contains(&key.to_uppercase())
+        } else {
+            self.list.contains(key)
+        };
+        listed != self.exclude
+    }
+
+    /// Copies the kept lines of `input` to `output`, returning how many there
+    /// were.
+    pub fn filter<R: BufRead, W: Write>(&self, mut input: R, output: &mut W) -> io::Result<u64> {
+        let mut kept = 0;
+        let mut buffer = Vec::new();
+        loop {
+            buffer.clear();
+            if input.read_until(b'\n', &mut buffer)? == 0 {
+                break;
+            }
+            let line = buffer.strip_suffix(b"\n").unwrap_or(&buffer);
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if self.keeps(&String::from_utf8_lossy(line)) {
+                output.write_all(&buffer)?;
+                kept += 1;
+            }
+        }
+        output.flush()?;
+        Ok(kept)
+    }
+}
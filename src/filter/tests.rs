@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+    use crate::filter::{KeySelector, LineFilter};
+    use tries::{SymbolTable, TernarySearchTrie};
+
+    fn list(keys: &[&str]) -> TernarySearchTrie<u32> {
+        let mut table = TernarySearchTrie::new();
+        for (index, key) in keys.iter().enumerate() {
+            table.put(key.to_string(), index as u32);
+        }
+        table
+    }
+
+    fn run(filter: &LineFilter, input: &str) -> String {
+        String::from_utf8(run_bytes(filter, input.as_bytes())).unwrap()
+    }
+
+    fn run_bytes(filter: &LineFilter, input: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+        filter.filter(input, &mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn test_keeps_listed_lines_in_order() {
+        let filter = LineFilter::from_table(list(&["sea", "she"]), KeySelector::Line, false, false);
+        assert_eq!(run(&filter, "she\nsells\nsea\nshe\n"), "she\nsea\nshe\n");
+    }
+
+    #[test]
+    fn test_exclude() {
+        let filter = LineFilter::from_table(list(&["SEA"]), KeySelector::Line, true, true);
+        assert_eq!(run(&filter, "she\nSea\n\nshore\n"), "she\n\nshore\n");
+    }
+
+    #[test]
+    fn test_key_field() {
+        let key = KeySelector::Field {
+            index: 2,
+            delimiter: ",".to_string(),
+        };
+        let filter = LineFilter::from_table(list(&["user_123"]), key, false, false);
+        assert_eq!(
+            run(&filter, "1,user_123,active\n2,user_124,active\n3\n"),
+            "1,user_123,active\n"
+        );
+    }
+
+    #[test]
+    fn test_lines_written_as_read() {
+        let filter =
+            LineFilter::from_table(list(&["sea", "shore"]), KeySelector::Line, false, false);
+        // Invalid UTF-8 does not end the input, and line endings are kept
+        assert_eq!(
+            run_bytes(&filter, b"sea\r\n\xff\xfe\nshe\nshore"),
+            b"sea\r\nshore"
+        );
+
+        let filter = LineFilter::from_table(list(&["sea"]), KeySelector::Line, false, true);
+        assert_eq!(run_bytes(&filter, b"\xffsea\r\nsea\r\n"), b"\xffsea\r\n");
+    }
+
+    #[test]
+    fn test_missing_list() {
+        let filter = LineFilter::new("missing-list.txt", KeySelector::Line, false, true);
+        assert_eq!(filter.err().unwrap().kind(), std::io::ErrorKind::NotFound);
+    }
+}
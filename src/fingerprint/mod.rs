@@ -0,0 +1,180 @@
+//! `--hash`: comparing lines by a 128-bit fingerprint instead of their text.
+//!
+//! A trie keyed by whole lines spends a node on every character, which is
+//! costly for multi-kilobyte lines. Here a hash map is keyed by the
+//! fingerprint of the normalized line itself, so each entry takes the same
+//! few bytes however long its line, and the value records where the line is
+//! so its text can be re-read for the report.
+//!
+//! Fingerprints are 128-bit FNV-1a, so an accidental collision is vanishingly
+//! unlikely; `--verify` re-reads both sides of every match to rule it out.
+
+use crate::archive::split_member;
+use crate::line_source::LineSource;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+const FNV_OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+/// Where a line is: its zero-based number, and the byte range of its text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LineLocation {
+    pub line: u32,
+    pub offset: u64,
+    pub len: u32,
+}
+
+pub fn fingerprint(text: &str) -> u128 {
+    extend_fingerprint(FNV_OFFSET_BASIS, text.as_bytes())
+}
+
+/// Continues `hash`, the fingerprint of some bytes, over `bytes`; start
+/// from `fingerprint("")` to hash a stream in pieces.
+pub fn extend_fingerprint(hash: u128, bytes: &[u8]) -> u128 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ byte as u128).wrapping_mul(FNV_PRIME)
+    })
+}
+
+fn normalize(text: &str, ignore_case: bool) -> String {
+    if ignore_case {
+        text.to_uppercase()
+    } else {
+        text.to_string()
+    }
+}
+
+/// The lines only in the first file and those only in the second.
+pub type Differences = (Vec<(u32, String)>, Vec<(u32, String)>);
+
+/// Re-reads lines of one file by location.
+pub struct LineReader {
+    file: File,
+    ignore_case: bool,
+}
+
+impl LineReader {
+    pub fn open(filename: &str, ignore_case: bool) -> io::Result<Self> {
+        let not_seekable = || {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--hash needs a regular file to re-read lines from",
+            )
+        };
+        if filename == "-" || split_member(filename).is_some() {
+            return Err(not_seekable());
+        }
+        let file = File::open(filename)?;
+        if !file.metadata()?.is_file() {
+            return Err(not_seekable());
+        }
+        Ok(LineReader { file, ignore_case })
+    }
+
+    /// Returns the normalized text at `location`.
+    pub fn read(&mut self, location: &LineLocation) -> io::Result<String> {
+        self.file.seek(SeekFrom::Start(location.offset))?;
+        let mut text = vec![0u8; location.len as usize];
+        self.file.read_exact(&mut text)?;
+        let text =
+            String::from_utf8(text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(normalize(&text, self.ignore_case))
+    }
+}
+
+/// The fingerprint table of one file, with a reader for its lines.
+pub struct FingerprintTable {
+    /// The location of each distinct line, by fingerprint
+    pub locations: HashMap<u128, LineLocation>,
+    /// Non-empty lines fingerprinted, counting repeats
+    pub total_lines: usize,
+    reader: LineReader,
+}
+
+impl FingerprintTable {
+    /// Fingerprints every line of `filename`. Repeated lines keep their last
+    /// location, as in the line trie. With `verify`, a repeated fingerprint
+    /// is checked against the earlier line's text.
+    pub fn build(filename: &str, ignore_case: bool, verify: bool) -> io::Result<Self> {
+        let mut reader = LineReader::open(filename, ignore_case)?;
+        let mut locations = HashMap::new();
+        let mut total_lines = 0;
+        let mut result = Ok(());
+
+        LineSource::open(filename)?.for_each_line_at(|index, offset, text| {
+            if text.is_empty() || result.is_err() {
+                return;
+            }
+            total_lines += 1;
+            let normalized = normalize(text, ignore_case);
+            let key = fingerprint(&normalized);
+            let location = LineLocation {
+                line: index as u32,
+                offset,
+                len: text.len() as u32,
+            };
+            if verify && let Some(earlier) = locations.get(&key) {
+                match reader.read(earlier) {
+                    Ok(earlier_text) if earlier_text != normalized => {
+                        result = Err(io::Error::other(format!(
+                            "fingerprint collision between lines {} and {}",
+                            earlier.line, location.line
+                        )));
+                    }
+                    Ok(_) => {}
+                    Err(error) => result = Err(error),
+                }
+            }
+            locations.insert(key, location);
+        })?;
+        result?;
+
+        Ok(FingerprintTable {
+            locations,
+            total_lines,
+            reader,
+        })
+    }
+}
+
+/// The lines of each table missing from the other, sorted by line number,
+/// with their text re-read. With `verify`, fingerprints found in both tables
+/// are checked to be the same text; a mismatch counts as a difference on
+/// both sides.
+pub fn compare_fingerprints(
+    first: &mut FingerprintTable,
+    second: &mut FingerprintTable,
+    verify: bool,
+) -> io::Result<Differences> {
+    let mut first_only = Vec::new();
+    let mut second_only = Vec::new();
+
+    for (key, location) in &first.locations {
+        match second.locations.get(key) {
+            None => first_only.push((location.line, first.reader.read(location)?)),
+            Some(other) if verify => {
+                let text = first.reader.read(location)?;
+                let other_text = second.reader.read(other)?;
+                if text != other_text {
+                    first_only.push((location.line, text));
+                    second_only.push((other.line, other_text));
+                }
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, location) in &second.locations {
+        if !first.locations.contains_key(key) {
+            second_only.push((location.line, second.reader.read(location)?));
+        }
+    }
+
+    first_only.sort_by_key(|k| k.0);
+    second_only.sort_by_key(|k| k.0);
+    Ok((first_only, second_only))
+}
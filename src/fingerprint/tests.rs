@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use crate::fingerprint::{FingerprintTable, LineLocation, compare_fingerprints, fingerprint};
+    use crate::test_util::write_temp;
+    use crate::{build_symbol_table, keys_missing_from};
+    use tries::{SymbolTable, TernarySearchTrie};
+
+    #[test]
+    fn test_fingerprint() {
+        // Published FNV-1a 128 test vectors
+        assert_eq!(fingerprint(""), 0x6c62272e07bb014262b821756295c58d);
+        assert_eq!(fingerprint("a"), 0xd228cb696f1a8caf78912b704e4a8964);
+        assert_ne!(fingerprint("ab"), fingerprint("ba"));
+    }
+
+    #[test]
+    fn test_matches_text_comparison() {
+        let long_line = format!("{{\"blob\": \"{}\"}}", "x".repeat(1000));
+        let first = write_temp(
+            "first",
+            format!("one\r\n{}\n\nTwo\nthree\none\nfünf\n", long_line),
+        );
+        let second = write_temp("second", format!("two\nFÜNF\n{}\nfour\n", long_line));
+        let (first, second) = (first.as_str(), second.as_str());
+
+        for ignore_case in [false, true] {
+            for verify in [false, true] {
+                let mut table_1 = TernarySearchTrie::new();
+                let mut table_2 = TernarySearchTrie::new();
+                build_symbol_table(first, &mut table_1, ignore_case).unwrap();
+                build_symbol_table(second, &mut table_2, ignore_case).unwrap();
+                let mut expected_first = keys_missing_from(&table_1, &table_2);
+                let mut expected_second = keys_missing_from(&table_2, &table_1);
+                expected_first.sort_by_key(|k| k.0);
+                expected_second.sort_by_key(|k| k.0);
+
+                let mut hashed_1 = FingerprintTable::build(first, ignore_case, verify).unwrap();
+                let mut hashed_2 = FingerprintTable::build(second, ignore_case, verify).unwrap();
+                assert_eq!(hashed_1.locations.len(), table_1.get_size());
+                let (first_only, second_only) =
+                    compare_fingerprints(&mut hashed_1, &mut hashed_2, verify).unwrap();
+
+                assert_eq!(first_only, expected_first);
+                assert_eq!(second_only, expected_second);
+            }
+        }
+    }
+
+    #[test]
+    fn test_footprint_independent_of_line_length() {
+        // Heap bytes the table keeps for its lines
+        let footprint = |table: &FingerprintTable| {
+            table.locations.capacity() * std::mem::size_of::<(u128, LineLocation)>()
+        };
+        let lines = |width: usize| -> String {
+            (0..500)
+                .map(|line| format!("{:0width$}\n", line, width = width))
+                .collect()
+        };
+        let short = write_temp("short", lines(8));
+        let long = write_temp("long", lines(4000));
+
+        let short = FingerprintTable::build(short.as_str(), false, false).unwrap();
+        let long = FingerprintTable::build(long.as_str(), false, false).unwrap();
+        assert_eq!(long.locations.len(), 500);
+        assert_eq!(footprint(&long), footprint(&short));
+    }
+
+    #[test]
+    fn test_rejects_stdin() {
+        assert!(FingerprintTable::build("-", false, false).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+//! Pairing near-identical differences for `--fuzzy N`.
+//!
+//! Lines only in the second file go into a trie, and each line only in the
+//! first file looks up its closest partner with
+//! [`FuzzySearch::get_keys_within_distance`], so candidates are found by
+//! walking shared prefixes rather than comparing every pair.
+
+use crate::ordered_diff::{Edit, diff};
+use tries::{FuzzySearch, SymbolTable, TernarySearchTrie};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+/// A line of the first file paired with its closest counterpart in the
+/// second, within the allowed number of edits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangedLine {
+    pub old_line: u32,
+    pub new_line: u32,
+    pub old_text: String,
+    pub new_text: String,
+    pub distance: usize,
+}
+
+/// Moves every line of `first_only` that has a partner in `second_only`
+/// within `max_distance` edits out of both lists and into the result.
+///
+/// Lines are paired greedily in the order of `first_only`, and each line of
+/// `second_only` is used at most once; ties go to the lexicographically
+/// smallest candidate.
+pub fn pair_near_matches(
+    first_only: &mut Vec<(u32, String)>,
+    second_only: &mut Vec<(u32, String)>,
+    max_distance: usize,
+) -> Vec<ChangedLine> {
+    let mut candidates = TernarySearchTrie::<u32>::new();
+    for (num, text) in second_only.iter() {
+        candidates.put(text.clone(), *num);
+    }
+
+    let mut changed_lines = Vec::new();
+    first_only.retain(|(num, text)| {
+        let closest = candidates
+            .get_keys_within_distance(text, max_distance)
+            .into_iter()
+            .min_by_key(|(_, distance)| *distance);
+        let Some((new_text, distance)) = closest else {
+            return true;
+        };
+
+        let new_line = candidates.get(&new_text).unwrap();
+        candidates.delete(&new_text);
+        changed_lines.push(ChangedLine {
+            old_line: *num,
+            new_line,
+            old_text: text.clone(),
+            new_text,
+            distance,
+        });
+        false
+    });
+
+    second_only.retain(|(_, text)| candidates.contains(text));
+    changed_lines
+}
+
+/// Runs of characters that are common to both sides, or only on one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Segment {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Splits a changed line into segments by a character-level diff of the old
+/// and new text, merging adjacent characters of the same kind.
+pub fn highlight(old_text: &str, new_text: &str) -> Vec<Segment> {
+    let old: Vec<char> = old_text.chars().collect();
+    let new: Vec<char> = new_text.chars().collect();
+    let old_ids: Vec<u32> = old.iter().map(|&c| c as u32).collect();
+    let new_ids: Vec<u32> = new.iter().map(|&c| c as u32).collect();
+
+    let mut segments: Vec<Segment> = Vec::new();
+    for edit in diff(&old_ids, &new_ids) {
+        let (c, segment) = match edit {
+            Edit::Equal(o, _) => (old[o], Segment::Same(String::new())),
+            Edit::Delete(o) => (old[o], Segment::Removed(String::new())),
+            Edit::Insert(n) => (new[n], Segment::Added(String::new())),
+        };
+        let extends_last = matches!(
+            (segments.last(), &segment),
+            (Some(Segment::Same(_)), Segment::Same(_))
+                | (Some(Segment::Removed(_)), Segment::Removed(_))
+                | (Some(Segment::Added(_)), Segment::Added(_))
+        );
+        if !extends_last {
+            segments.push(segment);
+        }
+        match segments.last_mut().unwrap() {
+            Segment::Same(text) | Segment::Removed(text) | Segment::Added(text) => text.push(c),
+        }
+    }
+    segments
+}
+
+/// Renders segments in the `wdiff` style: `[-removed-]{+added+}`.
+pub fn highlight_text(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            Segment::Same(text) => text.clone(),
+            Segment::Removed(text) => format!("[-{}-]", text),
+            Segment::Added(text) => format!("{{+{}+}}", text),
+        })
+        .collect()
+}
+
+/// Renders segments with `<del>` and `<ins>` tags.
+pub fn highlight_html(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            Segment::Same(text) => text.clone(),
+            Segment::Removed(text) => format!("<del>{}</del>", text),
+            Segment::Added(text) => format!("<ins>{}</ins>", text),
+        })
+        .collect()
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use crate::fuzzy::{ChangedLine, Segment, highlight, highlight_text, pair_near_matches};
+
+    fn lines(lines: &[(u32, &str)]) -> Vec<(u32, String)> {
+        lines
+            .iter()
+            .map(|(num, text)| (*num, text.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_pairs_closest_line() {
+        let mut first_only = lines(&[(0, "user_123,actve"), (4, "root")]);
+        let mut second_only = lines(&[(1, "user_124,active"), (2, "user_123,active")]);
+
+        let changed = pair_near_matches(&mut first_only, &mut second_only, 2);
+
+        assert_eq!(
+            changed,
+            [ChangedLine {
+                old_line: 0,
+                new_line: 2,
+                old_text: "user_123,actve".to_string(),
+                new_text: "user_123,active".to_string(),
+                distance: 1,
+            }]
+        );
+        assert_eq!(first_only, lines(&[(4, "root")]));
+        assert_eq!(second_only, lines(&[(1, "user_124,active")]));
+    }
+
+    #[test]
+    fn test_each_line_pairs_once() {
+        let mut first_only = lines(&[(0, "sea"), (1, "see")]);
+        let mut second_only = lines(&[(0, "sew")]);
+
+        let changed = pair_near_matches(&mut first_only, &mut second_only, 1);
+
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].old_text, "sea");
+        assert_eq!(first_only, lines(&[(1, "see")]));
+        assert!(second_only.is_empty());
+    }
+
+    #[test]
+    fn test_highlight() {
+        let segments = highlight("user_123,actve", "user_123,active");
+        assert_eq!(
+            segments,
+            [
+                Segment::Same("user_123,act".to_string()),
+                Segment::Added("i".to_string()),
+                Segment::Same("ve".to_string()),
+            ]
+        );
+        assert_eq!(
+            highlight_text(&highlight("shells", "shore")),
+            "sh{+or+}e[-lls-]"
+        );
+    }
+}
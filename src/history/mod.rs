@@ -0,0 +1,111 @@
+//! `history`: when each line came and went across a series of versions of a
+//! file, such as daily exports.
+//!
+//! The versions are read one at a time, oldest first; each may be a text
+//! file or a snapshot. A single trie keyed by line records the runs of
+//! consecutive versions each line was present in, so memory grows with the
+//! distinct lines ever seen rather than with the number of versions.
+
+use crate::build_symbol_table;
+use crate::snapshot::{is_snapshot, load_snapshot};
+use std::io;
+use tries::{SymbolTable, TernarySearchTrie};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+/// The versions one line was present in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineHistory {
+    pub text: String,
+    /// Half-open ranges of version indices, in order
+    pub runs: Vec<(usize, usize)>,
+}
+
+impl LineHistory {
+    /// Index of the first version holding the line.
+    pub fn appeared(&self) -> usize {
+        self.runs[0].0
+    }
+
+    /// Index of the version the line was last dropped in, if it is missing
+    /// from the latest one.
+    pub fn disappeared(&self, versions: usize) -> Option<usize> {
+        let (_, end) = *self.runs.last().unwrap();
+        (end < versions).then_some(end)
+    }
+
+    /// Whether the line came back after being dropped.
+    pub fn flapped(&self) -> bool {
+        self.runs.len() > 1
+    }
+}
+
+/// The history of every line that changed across `files`.
+pub struct Timeline {
+    pub files: Vec<String>,
+    /// Lines not present in every version, ordered by when they appeared,
+    /// then by text
+    pub lines: Vec<LineHistory>,
+    /// Lines present in every version
+    pub unchanged: usize,
+}
+
+impl Timeline {
+    pub fn flapped(&self) -> usize {
+        self.lines.iter().filter(|line| line.flapped()).count()
+    }
+}
+
+// The table of one version, from a snapshot or by reading the file
+fn load_version(filename: &str, ignore_case: bool) -> io::Result<TernarySearchTrie<u32>> {
+    if is_snapshot(filename) {
+        let (symbol_table, _) = load_snapshot(filename, ignore_case)?;
+        return Ok(symbol_table);
+    }
+    let mut symbol_table = TernarySearchTrie::new();
+    build_symbol_table(filename, &mut symbol_table, ignore_case)?;
+    Ok(symbol_table)
+}
+
+/// Reads `files` in order and builds the timeline of their lines.
+pub fn build_timeline(files: &[String], ignore_case: bool) -> io::Result<Timeline> {
+    let mut runs: TernarySearchTrie<Vec<(usize, usize)>> = TernarySearchTrie::new();
+
+    for (version, filename) in files.iter().enumerate() {
+        let symbol_table = load_version(filename, ignore_case)
+            .map_err(|error| io::Error::new(error.kind(), format!("{}: {}", filename, error)))?;
+        symbol_table.for_each_entry(&mut |key, _| {
+            let mut line_runs = runs.get(key).unwrap_or_default();
+            match line_runs.last_mut() {
+                // Present in the previous version too: the run goes on
+                Some((_, end)) if *end == version => *end += 1,
+                _ => line_runs.push((version, version + 1)),
+            }
+            runs.put(key.to_string(), line_runs);
+            true
+        });
+    }
+
+    let mut lines = Vec::new();
+    let mut unchanged = 0;
+    runs.for_each_entry(&mut |key, line_runs| {
+        if line_runs[..] == [(0, files.len())] {
+            unchanged += 1;
+        } else {
+            lines.push(LineHistory {
+                text: key.to_string(),
+                runs: line_runs.clone(),
+            });
+        }
+        true
+    });
+    // Stable, so lines appearing together stay in key order
+    lines.sort_by_key(LineHistory::appeared);
+
+    Ok(Timeline {
+        files: files.to_vec(),
+        lines,
+        unchanged,
+    })
+}
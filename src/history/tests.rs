@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use crate::history::{LineHistory, build_timeline};
+    use crate::render::json_string;
+    use crate::snapshot::create_snapshot;
+    use crate::test_util::write_temp;
+
+    fn history(text: &str, runs: &[(usize, usize)]) -> LineHistory {
+        LineHistory {
+            text: text.to_string(),
+            runs: runs.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_timeline() {
+        let days = [
+            write_temp("day1", "stable\ngone\nflappy\n"),
+            write_temp("day2", "stable\nnew\n"),
+            write_temp("day3", "Flappy\nstable\nnew\n\nstable\n"),
+        ];
+        let files: Vec<String> = days.iter().map(|day| day.as_str().to_string()).collect();
+
+        let timeline = build_timeline(&files, true).unwrap();
+        assert_eq!(timeline.unchanged, 1);
+        assert_eq!(
+            timeline.lines,
+            vec![
+                history("FLAPPY", &[(0, 1), (2, 3)]),
+                history("GONE", &[(0, 1)]),
+                history("NEW", &[(1, 3)]),
+            ]
+        );
+        assert_eq!(timeline.flapped(), 1);
+
+        let gone = &timeline.lines[1];
+        assert_eq!((gone.appeared(), gone.disappeared(3)), (0, Some(1)));
+        assert!(!gone.flapped());
+        let new = &timeline.lines[2];
+        assert_eq!((new.appeared(), new.disappeared(3)), (1, None));
+
+        // Case matters without `--ignore-case`
+        let timeline = build_timeline(&files, false).unwrap();
+        assert_eq!(timeline.lines[0], history("flappy", &[(0, 1)]));
+    }
+
+    #[test]
+    fn test_snapshots_and_missing_files() {
+        let day1_file = write_temp("snap-day1", "a\nb\n");
+        let day1_snap_file = write_temp("snap-day1.snap", "");
+        let day2_file = write_temp("snap-day2", "b\nc\n");
+        let (day1, day1_snap, day2) = (
+            day1_file.as_str().to_string(),
+            day1_snap_file.as_str().to_string(),
+            day2_file.as_str().to_string(),
+        );
+        create_snapshot(&day1, &day1_snap, false).unwrap();
+
+        let timeline = build_timeline(&[day1_snap.clone(), day2.clone()], false).unwrap();
+        assert_eq!(timeline.unchanged, 1);
+        assert_eq!(
+            timeline.lines,
+            vec![history("a", &[(0, 1)]), history("c", &[(1, 2)])]
+        );
+
+        assert!(build_timeline(&[day1_snap.clone(), day2.clone()], true).is_err());
+        let missing = format!("{}-missing", day2);
+        assert!(build_timeline(&[day1.clone(), missing], false).is_err());
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("plain"), "\"plain\"");
+        assert_eq!(
+            json_string("a \"quote\" \\ and\ttab\u{1}"),
+            "\"a \\\"quote\\\" \\\\ and\\ttab\\u0001\""
+        );
+    }
+}
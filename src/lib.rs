@@ -0,0 +1,107 @@
+//! File compare - fast compare lines in two files ignoring relative order.
+//!
+//! The command line tool is a thin wrapper over this crate: [`Comparer`]
+//! runs the default line-set comparison and returns a [`ComparisonResult`],
+//! which a [`Renderer`] such as [`TextRenderer`] or [`HtmlRenderer`] writes
+//! out. The symbol tables it can run on are in [`backend`].
+
+use crate::line_source::{LineSource, for_each_line_read};
+use std::io::{self, BufRead};
+use tries::SymbolTable;
+
+mod approximate;
+mod archive;
+pub mod backend;
+pub mod clap_parser;
+pub mod cli;
+pub mod comparer;
+mod directory;
+mod expr;
+mod external;
+mod filter;
+mod fingerprint;
+pub mod fuzzy;
+mod history;
+mod line_source;
+pub mod moves;
+mod ordered_diff;
+mod parallel;
+mod patch;
+mod patterns;
+mod prefix_groups;
+mod prefix_match;
+pub mod render;
+mod snapshot;
+pub mod stream;
+pub mod summary;
+#[cfg(test)]
+pub(crate) mod test_util;
+mod three_way;
+mod tokenizer;
+mod watch;
+
+pub use comparer::{Comparer, ComparisonResult, LoadedTable, Strategy};
+pub use render::{HtmlRenderer, Renderer, TextRenderer};
+
+// Every key of `symbol_table` that `other` lacks, paired with its value
+fn keys_missing_from<E: Clone, T: SymbolTable<E>>(symbol_table: &T, other: &T) -> Vec<(E, String)> {
+    let mut missing: Vec<(E, String)> = Vec::new();
+    for key in &symbol_table.get_all_keys() {
+        if !other.contains(key.as_ref()) {
+            let value = symbol_table.get(key.as_ref()).unwrap();
+            missing.push((value, key.clone()))
+        }
+    }
+    missing
+}
+
+// Puts every line of `filename` into `symbol_table`, returning how many
+// non-empty lines there were. An input that cannot be opened or read is an
+// error, never an empty table.
+fn build_symbol_table<T: SymbolTable<u32>>(
+    filename: &str,
+    symbol_table: &mut T,
+    ignore_case: bool,
+) -> io::Result<usize> {
+    let mut total_lines = 0;
+    LineSource::open(filename)?.for_each_line(|index, current_line| {
+        if put_line(symbol_table, index, current_line, ignore_case) {
+            total_lines += 1;
+        }
+    })?;
+    Ok(total_lines)
+}
+
+// Puts every line `reader` gives, such as an archive member's, into
+// `symbol_table`, returning how many non-empty lines there were
+fn read_symbol_table<R: BufRead, T: SymbolTable<u32>>(
+    reader: R,
+    symbol_table: &mut T,
+    ignore_case: bool,
+) -> io::Result<usize> {
+    let mut total_lines = 0;
+    for_each_line_read(reader, |index, _, line| {
+        if let Ok(current_line) = std::str::from_utf8(line)
+            && put_line(symbol_table, index, current_line, ignore_case)
+        {
+            total_lines += 1;
+        }
+    })?;
+    Ok(total_lines)
+}
+
+// Puts the line at `index` into `symbol_table`, returning whether it is
+// non-empty
+fn put_line<T: SymbolTable<u32>>(
+    symbol_table: &mut T,
+    index: usize,
+    current_line: &str,
+    ignore_case: bool,
+) -> bool {
+    if ignore_case {
+        symbol_table.put(current_line.to_uppercase(), index as u32);
+    } else {
+        symbol_table.put(current_line.to_string(), index as u32);
+    }
+    !current_line.is_empty()
+}
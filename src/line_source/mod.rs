@@ -0,0 +1,171 @@
+//! Reading the lines of an input without allocating a `String` per line.
+//!
+//! Regular files are memory-mapped and their line boundaries found in place,
+//! so each line reaches the caller as a `&str` slice of the mapping. Inputs
+//! that cannot be mapped - standard input given as `-`, pipes, archive
+//! members and empty files - fall back to a buffered reader that reuses one
+//! line buffer.
+//!
+//! Lines are split exactly as `BufRead::lines` splits them: on `\n`, with a
+//! `\r` before it also removed. A line that is not valid UTF-8 is skipped but
+//! still counted, so line numbers agree with `BufRead::lines`.
+
+use crate::archive::open_member;
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+pub enum LineSource {
+    Mapped(Mmap),
+    Buffered(Box<dyn BufRead>),
+}
+
+impl LineSource {
+    /// Opens `filename`, standard input for `-`, or the archive member an
+    /// `ARCHIVE:PATH` names.
+    pub fn open(filename: &str) -> io::Result<Self> {
+        if filename == "-" {
+            return Ok(LineSource::Buffered(Box::new(io::stdin().lock())));
+        }
+        if let Some(member) = open_member(filename) {
+            return Ok(LineSource::Buffered(Box::new(member?)));
+        }
+
+        let file = File::open(filename)?;
+        let metadata = file.metadata()?;
+        if metadata.is_file() && metadata.len() > 0 {
+            // SAFETY: the mapping is only read, and only while comparing; a
+            // file truncated underneath it is outside what we can guard
+            // against, as with any other reader
+            if let Ok(map) = unsafe { Mmap::map(&file) } {
+                return Ok(LineSource::Mapped(map));
+            }
+        }
+        Ok(LineSource::Buffered(Box::new(BufReader::new(file))))
+    }
+
+    /// Calls `f` with the zero-based index and text of every line.
+    pub fn for_each_line<F>(self, mut f: F) -> io::Result<()>
+    where
+        F: FnMut(usize, &str),
+    {
+        self.for_each_line_at(|index, _, line| f(index, line))
+    }
+
+    /// Like [`LineSource::for_each_line`], also passing the byte offset each
+    /// line starts at.
+    pub fn for_each_line_at<F>(self, mut f: F) -> io::Result<()>
+    where
+        F: FnMut(usize, u64, &str),
+    {
+        self.for_each_raw_line(|index, offset, line| {
+            if let Ok(line) = std::str::from_utf8(line) {
+                f(index, offset, line);
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Calls `f` with the index, offset and bytes of every line, valid UTF-8
+    /// or not, and returns whether the input ends with a newline. An empty
+    /// input counts as ending with one.
+    pub fn for_each_raw_line<F>(self, f: F) -> io::Result<bool>
+    where
+        F: FnMut(usize, u64, &[u8]),
+    {
+        match self {
+            LineSource::Mapped(map) => Ok(for_each_line_in(&map, f)),
+            LineSource::Buffered(reader) => for_each_line_read(reader, f),
+        }
+    }
+}
+
+/// Calls `f` with the index, offset and bytes of every line `reader` gives,
+/// as [`LineSource::for_each_raw_line`] does for an input it cannot map, and
+/// returns whether the input ends with a newline.
+pub fn for_each_line_read<R, F>(mut reader: R, mut f: F) -> io::Result<bool>
+where
+    R: BufRead,
+    F: FnMut(usize, u64, &[u8]),
+{
+    let mut index = 0;
+    let mut offset = 0;
+    let mut ends_with_newline = true;
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        let consumed = reader.read_until(b'\n', &mut buffer)?;
+        if consumed == 0 {
+            break;
+        }
+        let line = match buffer.strip_suffix(b"\n") {
+            Some(line) => trim_cr(line),
+            None => {
+                ends_with_newline = false;
+                &buffer[..]
+            }
+        };
+        f(index, offset, line);
+        offset += consumed as u64;
+        index += 1;
+    }
+    Ok(ends_with_newline)
+}
+
+/// Calls `f` with the index, offset and bytes of every line of `bytes`, as
+/// [`LineSource::for_each_raw_line`] does for a mapped file, and returns
+/// whether `bytes` ends with a newline.
+pub fn for_each_line_in<F>(bytes: &[u8], mut f: F) -> bool
+where
+    F: FnMut(usize, u64, &[u8]),
+{
+    let mut index = 0;
+    let mut offset = 0;
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let Some(end) = rest.iter().position(|&b| b == b'\n') else {
+            f(index, offset, rest);
+            return false;
+        };
+        f(index, offset, trim_cr(&rest[..end]));
+        rest = &rest[end + 1..];
+        offset += end as u64 + 1;
+        index += 1;
+    }
+    true
+}
+
+/// Every line of an input as text, for output that reproduces the input.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FileLines {
+    /// Lines that are not UTF-8 are decoded lossily rather than skipped, so
+    /// indices agree with the line numbers of [`LineSource::for_each_line`]
+    pub lines: Vec<String>,
+    /// Whether the last line lacks a terminating newline
+    pub missing_newline: bool,
+}
+
+impl FileLines {
+    /// Reads every line of `filename`, empty ones included.
+    pub fn read(filename: &str) -> io::Result<Self> {
+        let mut file = FileLines::default();
+        let ends_with_newline = LineSource::open(filename)?.for_each_raw_line(|_, _, line| {
+            file.lines.push(String::from_utf8_lossy(line).into_owned())
+        })?;
+        file.missing_newline = !ends_with_newline;
+        Ok(file)
+    }
+
+    /// Whether `index` is the last line and lacks a newline, so a diff must
+    /// follow it with `\ No newline at end of file`.
+    pub fn lacks_newline(&self, index: usize) -> bool {
+        self.missing_newline && index + 1 == self.lines.len()
+    }
+}
+
+fn trim_cr(line: &[u8]) -> &[u8] {
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
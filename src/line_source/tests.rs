@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod tests {
+    use crate::line_source::LineSource;
+    use crate::test_util::write_temp;
+    use std::fs::File;
+    use std::io::{BufRead, BufReader, Cursor};
+
+    const SAMPLES: &[&[u8]] = &[
+        b"",
+        b"one",
+        b"one\ntwo\n",
+        b"one\r\ntwo\r\nthree",
+        b"\n\nblank\n\n",
+        b"trailing cr\r",
+        b"bad \xff utf-8\nafter\n",
+        "caf\u{e9}\n\u{1f600}\n".as_bytes(),
+    ];
+
+    fn collect(source: LineSource) -> Vec<(usize, String)> {
+        let mut lines = Vec::new();
+        source
+            .for_each_line(|index, line| lines.push((index, line.to_string())))
+            .unwrap();
+        lines
+    }
+
+    #[test]
+    fn test_matches_buf_read_lines() {
+        for (index, sample) in SAMPLES.iter().enumerate() {
+            let path = write_temp(&index.to_string(), sample);
+            let expected: Vec<(usize, String)> = BufReader::new(File::open(&path).unwrap())
+                .lines()
+                .enumerate()
+                .filter_map(|(index, line)| line.ok().map(|line| (index, line)))
+                .collect();
+
+            let source = LineSource::open(path.as_str()).unwrap();
+            assert_eq!(matches!(source, LineSource::Mapped(_)), !sample.is_empty());
+            assert_eq!(collect(source), expected, "{:?}", sample);
+
+            let buffered = LineSource::Buffered(Box::new(Cursor::new(sample.to_vec())));
+            assert_eq!(collect(buffered), expected, "{:?}", sample);
+        }
+    }
+
+    #[test]
+    fn test_offsets_point_at_line_text() {
+        for (index, sample) in SAMPLES.iter().enumerate() {
+            let path = write_temp(&index.to_string(), sample);
+            let sources = [
+                LineSource::open(path.as_str()).unwrap(),
+                LineSource::Buffered(Box::new(Cursor::new(sample.to_vec()))),
+            ];
+            for source in sources {
+                source
+                    .for_each_line_at(|_, offset, line| {
+                        let start = offset as usize;
+                        assert_eq!(&sample[start..start + line.len()], line.as_bytes());
+                    })
+                    .unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_missing_file() {
+        assert!(LineSource::open("/nonexistent/file_compare/input").is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! Telling moved lines apart from added and removed ones, for `--moves`.
+//!
+//! A line present in both files keeps its relative position if it belongs to
+//! the longest run of common lines appearing in the same order in both. Every
+//! other common line has been moved.
+
+use tries::SymbolTable;
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+/// A line found in both files, out of order relative to the others.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MovedLine {
+    pub old_line: u32,
+    pub new_line: u32,
+    pub text: String,
+}
+
+/// Finds the common lines of two symbol tables built by `build_symbol_table`
+/// that changed relative position, sorted by their line in the first file.
+pub fn find_moved_lines<T: SymbolTable<u32>>(
+    symbol_table_1: &T,
+    symbol_table_2: &T,
+) -> Vec<MovedLine> {
+    let mut common: Vec<MovedLine> = Vec::new();
+    for key in &symbol_table_1.get_all_keys() {
+        if let Some(new_line) = symbol_table_2.get(key.as_ref()) {
+            let old_line = symbol_table_1.get(key.as_ref()).unwrap();
+            common.push(MovedLine {
+                old_line,
+                new_line,
+                text: key.clone(),
+            })
+        }
+    }
+    common.sort_by_key(|line| line.old_line);
+
+    let new_lines: Vec<u32> = common.iter().map(|line| line.new_line).collect();
+    let mut in_order = vec![false; common.len()];
+    for i in longest_increasing_subsequence(&new_lines) {
+        in_order[i] = true;
+    }
+
+    common
+        .into_iter()
+        .zip(in_order)
+        .filter(|(_, in_order)| !in_order)
+        .map(|(line, _)| line)
+        .collect()
+}
+
+// Indices of one longest strictly increasing subsequence of `values`, found by
+// patience sorting in O(n log n)
+fn longest_increasing_subsequence(values: &[u32]) -> Vec<usize> {
+    // tails[len] is the index of the smallest value ending a run of len + 1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous: Vec<Option<usize>> = vec![None; values.len()];
+
+    for (i, &value) in values.iter().enumerate() {
+        let len = tails.partition_point(|&t| values[t] < value);
+        if len > 0 {
+            previous[i] = Some(tails[len - 1]);
+        }
+        if len == tails.len() {
+            tails.push(i);
+        } else {
+            tails[len] = i;
+        }
+    }
+
+    let mut result = Vec::with_capacity(tails.len());
+    let mut current = tails.last().copied();
+    while let Some(i) = current {
+        result.push(i);
+        current = previous[i];
+    }
+    result.reverse();
+    result
+}
@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod tests {
+    use crate::moves::{MovedLine, find_moved_lines, longest_increasing_subsequence};
+    use tries::{SymbolTable, TernarySearchTrie};
+
+    fn build(lines: &[&str]) -> TernarySearchTrie<u32> {
+        let mut table = TernarySearchTrie::new();
+        for (index, line) in lines.iter().enumerate() {
+            table.put(line.to_string(), index as u32);
+        }
+        table
+    }
+
+    #[test]
+    fn test_longest_increasing_subsequence() {
+        assert_eq!(
+            longest_increasing_subsequence(&[3, 1, 4, 1, 5, 9, 2, 6]),
+            [1, 2, 4, 7]
+        );
+        assert!(longest_increasing_subsequence(&[]).is_empty());
+        assert_eq!(longest_increasing_subsequence(&[5, 4, 3]).len(), 1);
+    }
+
+    #[test]
+    fn test_moved_line() {
+        let first = build(&["she", "sells", "sea", "shells", "by"]);
+        let second = build(&["she", "sea", "shells", "sells", "shore"]);
+
+        assert_eq!(
+            find_moved_lines(&first, &second),
+            [MovedLine {
+                old_line: 1,
+                new_line: 3,
+                text: "sells".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_shifted_lines_are_not_moved() {
+        let first = build(&["she", "sells", "sea"]);
+        let second = build(&["new", "she", "sells", "sea"]);
+
+        assert!(find_moved_lines(&first, &second).is_empty());
+    }
+}
@@ -0,0 +1,334 @@
+//! The order-aware comparison behind `--ordered`.
+//!
+//! Lines are first interned into integer IDs through a shared
+//! [`TernarySearchTrie`], so the diff itself compares `u32`s rather than
+//! strings. The edit script comes from Myers' O(ND) algorithm in its
+//! linear-space form, and is then grouped into unified-diff hunks.
+
+use crate::line_source::FileLines;
+use std::io;
+use std::ops::{Index, IndexMut, Range};
+use tries::{SymbolTable, TernarySearchTrie};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+// The trie cannot store the empty string, so empty lines get a reserved ID
+const EMPTY_LINE_ID: u32 = 0;
+
+/// A file read for the ordered comparison: its lines as interned IDs, and the
+/// original text for output.
+pub struct InternedFile {
+    pub ids: Vec<u32>,
+    pub text: FileLines,
+}
+
+/// Maps each distinct line to a small integer, shared between the files being
+/// compared so equal lines get equal IDs.
+pub struct Interner {
+    symbol_table: TernarySearchTrie<u32>,
+    ignore_case: bool,
+}
+
+impl Interner {
+    pub fn new(ignore_case: bool) -> Self {
+        Interner {
+            symbol_table: TernarySearchTrie::new(),
+            ignore_case,
+        }
+    }
+
+    pub fn intern(&mut self, line: &str) -> u32 {
+        let key = if self.ignore_case {
+            line.to_uppercase()
+        } else {
+            line.to_string()
+        };
+        if key.is_empty() {
+            return EMPTY_LINE_ID;
+        }
+        if let Some(id) = self.symbol_table.get(&key) {
+            return id;
+        }
+        let id = self.symbol_table.get_size() as u32 + 1;
+        self.symbol_table.put(key, id);
+        id
+    }
+
+    pub fn intern_file(&mut self, filename: &str) -> io::Result<InternedFile> {
+        let text = FileLines::read(filename)?;
+        let ids = text
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                if text.lacks_newline(index) {
+                    // Differs from the same line ending in a newline, as in
+                    // `diff -u`; no line can hold one, so the key is unique
+                    self.intern(&format!("{}\n", line))
+                } else {
+                    self.intern(line)
+                }
+            })
+            .collect();
+        Ok(InternedFile { ids, text })
+    }
+}
+
+/// One step of an edit script turning the old sequence into the new one.
+/// Indices are zero-based positions in the respective sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edit {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Computes a shortest edit script from `old` to `new`. Within each run of
+/// changes deletions come before insertions, as `diff -u` prints them.
+pub fn diff(old: &[u32], new: &[u32]) -> Vec<Edit> {
+    let max_d = (old.len() + new.len()).div_ceil(2) + 1;
+    let mut vf = V::new(max_d);
+    let mut vb = V::new(max_d);
+    let mut edits = Vec::new();
+    conquer(
+        old,
+        0..old.len(),
+        new,
+        0..new.len(),
+        &mut vf,
+        &mut vb,
+        &mut edits,
+    );
+    for changes in edits.split_mut(|edit| matches!(edit, Edit::Equal(..))) {
+        // Stable, so each kind keeps its order
+        changes.sort_by_key(|edit| matches!(edit, Edit::Insert(_)));
+    }
+    edits
+}
+
+// A diagonal-indexed vector: `k` runs from `-max_d` to `max_d`
+struct V {
+    offset: isize,
+    v: Vec<usize>,
+}
+
+impl V {
+    fn new(max_d: usize) -> Self {
+        V {
+            offset: max_d as isize + 1,
+            v: vec![0; 2 * max_d + 3],
+        }
+    }
+}
+
+impl Index<isize> for V {
+    type Output = usize;
+
+    fn index(&self, k: isize) -> &usize {
+        &self.v[(k + self.offset) as usize]
+    }
+}
+
+impl IndexMut<isize> for V {
+    fn index_mut(&mut self, k: isize) -> &mut usize {
+        &mut self.v[(k + self.offset) as usize]
+    }
+}
+
+fn common_prefix_len(old: &[u32], new: &[u32]) -> usize {
+    old.iter().zip(new).take_while(|(a, b)| a == b).count()
+}
+
+fn common_suffix_len(old: &[u32], new: &[u32]) -> usize {
+    old.iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count()
+}
+
+// Runs the forward and backward searches towards each other until they
+// overlap, and returns the point where the shortest path crosses between them
+fn find_middle_snake(old: &[u32], new: &[u32], vf: &mut V, vb: &mut V) -> Option<(usize, usize)> {
+    let n = old.len();
+    let m = new.len();
+    let delta = n as isize - m as isize;
+    let odd = delta & 1 == 1;
+
+    vf[1] = 0;
+    vb[1] = 0;
+
+    let d_max = (n + m).div_ceil(2) as isize + 1;
+    for d in 0..d_max {
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && vf[k - 1] < vf[k + 1]) {
+                vf[k + 1]
+            } else {
+                vf[k - 1] + 1
+            };
+            let y = (x as isize - k) as usize;
+            let (x0, y0) = (x, y);
+            if x < n && y < m {
+                x += common_prefix_len(&old[x..], &new[y..]);
+            }
+            vf[k] = x;
+            if odd && (k - delta).abs() < d && vf[k] + vb[-(k - delta)] >= n {
+                return Some((x0, y0));
+            }
+        }
+
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && vb[k - 1] < vb[k + 1]) {
+                vb[k + 1]
+            } else {
+                vb[k - 1] + 1
+            };
+            let mut y = (x as isize - k) as usize;
+            if x < n && y < m {
+                let advance = common_suffix_len(&old[..n - x], &new[..m - y]);
+                x += advance;
+                y += advance;
+            }
+            vb[k] = x;
+            if !odd && (k - delta).abs() <= d && vb[k] + vf[-(k - delta)] >= n {
+                return Some((n - x, m - y));
+            }
+        }
+    }
+
+    None
+}
+
+// Strips the common ends, then splits the remainder at the middle snake and
+// recurses into both halves
+fn conquer(
+    old: &[u32],
+    mut old_range: Range<usize>,
+    new: &[u32],
+    mut new_range: Range<usize>,
+    vf: &mut V,
+    vb: &mut V,
+    edits: &mut Vec<Edit>,
+) {
+    let prefix = common_prefix_len(&old[old_range.clone()], &new[new_range.clone()]);
+    for i in 0..prefix {
+        edits.push(Edit::Equal(old_range.start + i, new_range.start + i));
+    }
+    old_range.start += prefix;
+    new_range.start += prefix;
+
+    let suffix = common_suffix_len(&old[old_range.clone()], &new[new_range.clone()]);
+    old_range.end -= suffix;
+    new_range.end -= suffix;
+
+    if old_range.is_empty() {
+        edits.extend(new_range.clone().map(Edit::Insert));
+    } else if new_range.is_empty() {
+        edits.extend(old_range.clone().map(Edit::Delete));
+    } else if let Some((x, y)) =
+        find_middle_snake(&old[old_range.clone()], &new[new_range.clone()], vf, vb)
+    {
+        let (x, y) = (old_range.start + x, new_range.start + y);
+        conquer(
+            old,
+            old_range.start..x,
+            new,
+            new_range.start..y,
+            vf,
+            vb,
+            edits,
+        );
+        conquer(old, x..old_range.end, new, y..new_range.end, vf, vb, edits);
+    } else {
+        edits.extend(old_range.clone().map(Edit::Delete));
+        edits.extend(new_range.clone().map(Edit::Insert));
+    }
+
+    for i in 0..suffix {
+        edits.push(Edit::Equal(old_range.end + i, new_range.end + i));
+    }
+}
+
+/// A run of edits plus surrounding context, as printed under one `@@` header.
+/// Starts are zero-based indices of the hunk's first line on each side.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+    pub edits: Vec<Edit>,
+}
+
+impl Hunk {
+    /// The `@@ -a,b +c,d @@` line, with one-based line numbers as in GNU diff.
+    pub fn header(&self) -> String {
+        format!(
+            "@@ -{} +{} @@",
+            Self::range(self.old_start, self.old_len),
+            Self::range(self.new_start, self.new_len)
+        )
+    }
+
+    fn range(start: usize, len: usize) -> String {
+        match len {
+            // An empty range names the line before it
+            0 => format!("{},0", start),
+            1 => format!("{}", start + 1),
+            _ => format!("{},{}", start + 1, len),
+        }
+    }
+}
+
+/// Groups an edit script into hunks keeping `context` unchanged lines around
+/// each change; changes closer than twice that share a hunk.
+pub fn build_hunks(edits: &[Edit], context: usize) -> Vec<Hunk> {
+    let changes: Vec<usize> = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, edit)| !matches!(edit, Edit::Equal(..)))
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut groups: Vec<Range<usize>> = Vec::new();
+    for &i in &changes {
+        let start = i.saturating_sub(context);
+        let end = (i + context + 1).min(edits.len());
+        match groups.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => groups.push(start..end),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|range| {
+            let before = &edits[..range.start];
+            let edits = edits[range].to_vec();
+            Hunk {
+                old_start: count_old(before),
+                old_len: count_old(&edits),
+                new_start: count_new(before),
+                new_len: count_new(&edits),
+                edits,
+            }
+        })
+        .collect()
+}
+
+// Lines of the old sequence an edit run covers
+fn count_old(edits: &[Edit]) -> usize {
+    edits
+        .iter()
+        .filter(|edit| !matches!(edit, Edit::Insert(_)))
+        .count()
+}
+
+// Lines of the new sequence an edit run covers
+fn count_new(edits: &[Edit]) -> usize {
+    edits
+        .iter()
+        .filter(|edit| !matches!(edit, Edit::Delete(_)))
+        .count()
+}
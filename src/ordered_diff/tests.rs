@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod tests {
+    use crate::ordered_diff::{Edit, Interner, build_hunks, diff};
+    use crate::render::write_unified_diff;
+    use crate::test_util::temp_path;
+    use std::process::Command;
+
+    // Applies the diff of `first` against `second` to a copy of `first` with
+    // `patch`, giving the patched bytes
+    fn patched(name: &str, first: &str, second: &str) -> Vec<u8> {
+        let mut interner = Interner::new(false);
+        let old = interner.intern_file(first).unwrap();
+        let new = interner.intern_file(second).unwrap();
+        let hunks = build_hunks(&diff(&old.ids, &new.ids), 3);
+        let mut patch = Vec::new();
+        write_unified_diff(&mut patch, first, second, &old.text, &new.text, &hunks).unwrap();
+
+        let copy = temp_path(&format!("{}.txt", name));
+        let patch_file = temp_path(&format!("{}.diff", name));
+        std::fs::copy(first, &copy).unwrap();
+        std::fs::write(&patch_file, patch).unwrap();
+        let status = Command::new("patch")
+            .args(["--silent", "--force", copy.as_str(), patch_file.as_str()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        std::fs::read(&copy).unwrap()
+    }
+
+    fn intern(interner: &mut Interner, text: &str) -> Vec<u32> {
+        text.chars()
+            .map(|c| interner.intern(&c.to_string()))
+            .collect()
+    }
+
+    // Replays an edit script, checking it really turns `old` into `new`
+    fn apply(old: &[u32], new: &[u32], edits: &[Edit]) -> Vec<u32> {
+        let mut result = Vec::new();
+        let (mut next_old, mut next_new) = (0, 0);
+        for edit in edits {
+            match *edit {
+                Edit::Equal(o, n) => {
+                    assert_eq!((o, n), (next_old, next_new));
+                    assert_eq!(old[o], new[n]);
+                    result.push(old[o]);
+                    next_old += 1;
+                    next_new += 1;
+                }
+                Edit::Delete(o) => {
+                    assert_eq!(o, next_old);
+                    next_old += 1;
+                }
+                Edit::Insert(n) => {
+                    assert_eq!(n, next_new);
+                    result.push(new[n]);
+                    next_new += 1;
+                }
+            }
+        }
+        assert_eq!((next_old, next_new), (old.len(), new.len()));
+        result
+    }
+
+    fn edit_distance(edits: &[Edit]) -> usize {
+        edits
+            .iter()
+            .filter(|edit| !matches!(edit, Edit::Equal(..)))
+            .count()
+    }
+
+    #[test]
+    fn test_interning() {
+        let mut interner = Interner::new(true);
+        let sea = interner.intern("sea");
+        assert_eq!(interner.intern("SEA"), sea);
+        assert_ne!(interner.intern("she"), sea);
+        assert_eq!(interner.intern(""), interner.intern(""));
+        assert_ne!(interner.intern(""), sea);
+    }
+
+    #[test]
+    fn test_shortest_edit_script() {
+        let cases = [
+            ("abcabba", "cbabac", 5),
+            ("", "", 0),
+            ("abc", "", 3),
+            ("", "abc", 3),
+            ("abc", "abc", 0),
+            ("abcdef", "abXdef", 2),
+            ("xaxbxc", "abc", 3),
+            ("sea shells", "she sells sea", 7),
+        ];
+        for (a, b, expected) in cases {
+            let mut interner = Interner::new(false);
+            let old = intern(&mut interner, a);
+            let new = intern(&mut interner, b);
+            let edits = diff(&old, &new);
+            assert_eq!(apply(&old, &new, &edits), new, "{} -> {}", a, b);
+            assert_eq!(edit_distance(&edits), expected, "{} -> {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_hunks() {
+        let mut interner = Interner::new(false);
+        let old = intern(&mut interner, "abcdefghijklmnop");
+        let new = intern(&mut interner, "abcdXfghijklmnoPq");
+        let hunks = build_hunks(&diff(&old, &new), 2);
+
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].header(), "@@ -3,5 +3,5 @@");
+        assert_eq!(hunks[1].header(), "@@ -14,3 +14,4 @@");
+
+        let hunks = build_hunks(&diff(&old, &new), 5);
+        assert_eq!(hunks.len(), 1);
+    }
+
+    #[test]
+    fn test_hunk_headers_for_one_sided_changes() {
+        let mut interner = Interner::new(false);
+        let old = intern(&mut interner, "ab");
+        let new = intern(&mut interner, "abc");
+        assert_eq!(
+            build_hunks(&diff(&old, &new), 0)[0].header(),
+            "@@ -2,0 +3 @@"
+        );
+        assert_eq!(
+            build_hunks(&diff(&new, &old), 0)[0].header(),
+            "@@ -3 +2,0 @@"
+        );
+        assert!(build_hunks(&diff(&old, &old), 3).is_empty());
+    }
+
+    #[test]
+    fn test_deletions_before_insertions() {
+        let mut interner = Interner::new(false);
+        let old = intern(&mut interner, "abcd");
+        let new = intern(&mut interner, "aXYd");
+        let edits = diff(&old, &new);
+        assert_eq!(
+            edits,
+            vec![
+                Edit::Equal(0, 0),
+                Edit::Delete(1),
+                Edit::Delete(2),
+                Edit::Insert(1),
+                Edit::Insert(2),
+                Edit::Equal(3, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_patch_applies() {
+        assert_eq!(
+            patched(
+                "fixtures",
+                "src/test_data/first.txt",
+                "src/test_data/second.txt"
+            ),
+            std::fs::read("src/test_data/second.txt").unwrap()
+        );
+
+        // Only the final newline differs
+        let with_newline = temp_path("with-newline");
+        let without_newline = temp_path("without-newline");
+        std::fs::write(&with_newline, "a\nb\n").unwrap();
+        std::fs::write(&without_newline, "a\nb").unwrap();
+        assert_eq!(
+            patched(
+                "drop-newline",
+                with_newline.as_str(),
+                without_newline.as_str()
+            ),
+            b"a\nb"
+        );
+        assert_eq!(
+            patched(
+                "add-newline",
+                without_newline.as_str(),
+                with_newline.as_str()
+            ),
+            b"a\nb\n"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Concurrent construction of the line symbol tables for `--threads`.
+//!
+//! Both files load at the same time, and a large file is further split into
+//! byte ranges that start right after a newline. The file is mapped once and
+//! each range scanned in place into its own trie, with line numbers counted
+//! from the start of the range; the tries
+//! are then merged in file order, shifting every line number by the lines of
+//! the ranges before it. Later ranges overwrite earlier ones, so a repeated
+//! line keeps its last line number exactly as `build_symbol_table` does.
+
+use crate::build_symbol_table;
+use crate::line_source::{LineSource, for_each_line_in};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::thread;
+use tries::SymbolTable;
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+// Files are only split into ranges of at least this many bytes
+const MIN_RANGE_BYTES: u64 = 1 << 20;
+
+/// A loaded table with its count of non-empty lines.
+pub type Loaded<T> = io::Result<(T, usize)>;
+
+/// Builds the symbol tables of both files at once, sharing `threads` between
+/// them. Each comes with its count of non-empty lines, or the error reading
+/// it.
+pub fn build_symbol_tables<T: SymbolTable<u32> + Default + Send>(
+    first: &str,
+    second: &str,
+    ignore_case: bool,
+    threads: usize,
+) -> (Loaded<T>, Loaded<T>) {
+    if threads < 2 {
+        return (
+            build_table(first, ignore_case, 1),
+            build_table(second, ignore_case, 1),
+        );
+    }
+
+    let first_threads = threads / 2;
+    let second_threads = threads - first_threads;
+    thread::scope(|scope| {
+        let first = scope.spawn(|| build_table(first, ignore_case, first_threads));
+        let second = build_table(second, ignore_case, second_threads);
+        (first.join().expect("loader thread panicked"), second)
+    })
+}
+
+/// Builds the symbol table of `filename` on up to `threads` threads,
+/// returning it with the number of non-empty lines read.
+pub fn build_table<T: SymbolTable<u32> + Default + Send>(
+    filename: &str,
+    ignore_case: bool,
+    threads: usize,
+) -> io::Result<(T, usize)> {
+    match split_ranges(filename, threads, MIN_RANGE_BYTES) {
+        Ok(ranges) if ranges.len() > 1 => build_ranges(filename, &ranges, ignore_case),
+        // Small files, and inputs that cannot be split here such as standard
+        // input, take the sequential path, which reports any read error
+        _ => {
+            let mut symbol_table = T::default();
+            let total_lines = build_symbol_table(filename, &mut symbol_table, ignore_case)?;
+            Ok((symbol_table, total_lines))
+        }
+    }
+}
+
+/// Splits `filename` into at most `parts` byte ranges of at least
+/// `min_bytes`, each ending just after a newline or at the end of the file.
+pub fn split_ranges(filename: &str, parts: usize, min_bytes: u64) -> io::Result<Vec<(u64, u64)>> {
+    let mut file = File::open(filename)?;
+    let len = file.metadata()?.len();
+    let parts = (parts as u64).min(len / min_bytes.max(1)).max(1);
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for part in 1..parts {
+        let target = len * part / parts;
+        if target <= start {
+            continue;
+        }
+        // Extend the range to the end of the line `target` falls in
+        file.seek(SeekFrom::Start(target - 1))?;
+        let mut rest_of_line = Vec::new();
+        let read = BufReader::new(&mut file).read_until(b'\n', &mut rest_of_line)?;
+        let end = target - 1 + read as u64;
+        if end >= len {
+            break;
+        }
+        ranges.push((start, end));
+        start = end;
+    }
+    ranges.push((start, len));
+    Ok(ranges)
+}
+
+/// Builds one trie per range on its own thread and merges them, returning
+/// the merged trie and the number of non-empty lines.
+pub fn build_ranges<T: SymbolTable<u32> + Default + Send>(
+    filename: &str,
+    ranges: &[(u64, u64)],
+    ignore_case: bool,
+) -> io::Result<(T, usize)> {
+    let LineSource::Mapped(map) = LineSource::open(filename)? else {
+        // Only a mapped file can be shared between the threads
+        let mut symbol_table = T::default();
+        let total_lines = build_symbol_table(filename, &mut symbol_table, ignore_case)?;
+        return Ok((symbol_table, total_lines));
+    };
+    let chunks: Vec<(T, u32, usize)> = thread::scope(|scope| {
+        let handles: Vec<_> = ranges
+            .iter()
+            .map(|&(start, end)| {
+                let range = &map[start as usize..end as usize];
+                scope.spawn(move || build_range(range, ignore_case))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("loader thread panicked"))
+            .collect()
+    });
+
+    let mut chunks = chunks.into_iter();
+    let Some((mut merged, mut line_offset, mut total_lines)) = chunks.next() else {
+        return Ok((T::default(), 0));
+    };
+    for (chunk, line_count, chunk_total) in chunks {
+        for key in chunk.get_all_keys() {
+            let line = chunk.get(&key).unwrap();
+            merged.put(key, line_offset + line);
+        }
+        line_offset += line_count;
+        total_lines += chunk_total;
+    }
+    Ok((merged, total_lines))
+}
+
+// Reads the lines of one range of the mapped file, returning their trie, how
+// many lines the range holds and how many of those are non-empty
+fn build_range<T: SymbolTable<u32> + Default>(range: &[u8], ignore_case: bool) -> (T, u32, usize) {
+    let mut symbol_table = T::default();
+    let mut line_count = 0;
+    let mut total_lines = 0;
+    for_each_line_in(range, |index, _, line| {
+        line_count = index as u32 + 1;
+        let Ok(current_line) = std::str::from_utf8(line) else {
+            return;
+        };
+        if !current_line.is_empty() {
+            total_lines += 1;
+        }
+        if ignore_case {
+            symbol_table.put(current_line.to_uppercase(), index as u32);
+        } else {
+            symbol_table.put(current_line.to_string(), index as u32);
+        }
+    });
+    (symbol_table, line_count, total_lines)
+}
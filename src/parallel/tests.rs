@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+    use crate::build_symbol_table;
+    use crate::parallel::{build_ranges, split_ranges};
+    use crate::test_util::write_temp;
+    use tries::{SymbolTable, TernarySearchTrie};
+
+    fn entries(table: &TernarySearchTrie<u32>) -> Vec<(String, u32)> {
+        table
+            .get_all_keys()
+            .into_iter()
+            .map(|key| {
+                let line = table.get(&key).unwrap();
+                (key, line)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ranges_end_after_newlines() {
+        let path = write_temp("ranges", "alpha\nbeta\ngamma\ndelta\nepsilon\nzeta");
+        let filename = path.as_str();
+        let ranges = split_ranges(filename, 4, 1).unwrap();
+        let contents = std::fs::read(&path).unwrap();
+
+        assert_eq!(ranges.first().unwrap().0, 0);
+        assert_eq!(ranges.last().unwrap().1, contents.len() as u64);
+        for pair in ranges.windows(2) {
+            assert_eq!(pair[0].1, pair[1].0);
+            assert_eq!(contents[pair[0].1 as usize - 1], b'\n');
+        }
+    }
+
+    #[test]
+    fn test_small_files_are_not_split() {
+        let path = write_temp("small", "one\ntwo\n");
+        let ranges = split_ranges(path.as_str(), 8, 1 << 20).unwrap();
+        assert_eq!(ranges, vec![(0, 8)]);
+    }
+
+    #[test]
+    fn test_matches_sequential_build() {
+        // Repeats across ranges must keep their last line number, and blank
+        // and invalid UTF-8 lines must still be counted
+        let contents: Vec<u8> = (0..500)
+            .flat_map(|i| match i % 7 {
+                0 => b"\n".to_vec(),
+                1 => format!("Repeat {}\r\n", i % 3).into_bytes(),
+                2 => b"\xff\xfe\n".to_vec(),
+                _ => format!("line {}\n", i % 120).into_bytes(),
+            })
+            .collect();
+        let path = write_temp("matches", &contents);
+        let filename = path.as_str();
+
+        for ignore_case in [false, true] {
+            let mut expected = TernarySearchTrie::new();
+            let expected_total = build_symbol_table(filename, &mut expected, ignore_case).unwrap();
+            for parts in [1, 2, 3, 8, 64] {
+                let ranges = split_ranges(filename, parts, 1).unwrap();
+                let (actual, total): (TernarySearchTrie<u32>, usize) =
+                    build_ranges(filename, &ranges, ignore_case).unwrap();
+                assert_eq!(entries(&actual), entries(&expected), "{} parts", parts);
+                assert_eq!(total, expected_total, "{} parts", parts);
+            }
+        }
+    }
+}
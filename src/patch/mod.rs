@@ -0,0 +1,145 @@
+//! Planning the edits behind `--emit-patch`: what to append to the second
+//! file, and optionally what to delete from it, so that it ends up containing
+//! every line of the first.
+
+use crate::line_source::FileLines;
+use crate::ordered_diff::Edit;
+use std::io;
+use std::ops::Range;
+use tries::SymbolTable;
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PatchPlan {
+    /// Zero-based indices of the second file's lines to delete, ascending
+    pub removed: Vec<usize>,
+    /// Original text of the first file's lines to append, in first-file order
+    pub appended: Vec<String>,
+    /// Number of lines in the second file before patching
+    pub original_len: usize,
+    /// Whether the second file's last line lacks a newline
+    pub missing_newline: bool,
+}
+
+impl PatchPlan {
+    /// Builds the plan from the report's `words_in_first_not_in_second` and the
+    /// full contents of both files, read with [`FileLines::read`] so that line
+    /// numbers agree with the report's.
+    ///
+    /// Lines are appended with their original text even when `ignore_case`
+    /// upper-cased the keys. With `remove_extras`, every line of the second
+    /// file whose key is absent from `symbol_table_1` is deleted, duplicates
+    /// included; empty lines, which are never compared, are kept. A line
+    /// number past the end of `first_lines`, left by a first file that
+    /// changed since the report was made, is an error.
+    pub fn new<T: SymbolTable<u32>>(
+        first_lines: &[String],
+        second: &FileLines,
+        words_in_first_not_in_second: &[(u32, String)],
+        symbol_table_1: &T,
+        ignore_case: bool,
+        remove_extras: bool,
+    ) -> io::Result<Self> {
+        let appended = words_in_first_not_in_second
+            .iter()
+            .map(|(num, _)| {
+                first_lines.get(*num as usize).cloned().ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("the first file has no line {} any more", num + 1),
+                    )
+                })
+            })
+            .collect::<io::Result<_>>()?;
+
+        let mut removed = Vec::new();
+        if remove_extras {
+            for (index, line) in second.lines.iter().enumerate() {
+                let key = if ignore_case {
+                    line.to_uppercase()
+                } else {
+                    line.clone()
+                };
+                if !key.is_empty() && !symbol_table_1.contains(&key) {
+                    removed.push(index);
+                }
+            }
+        }
+
+        Ok(PatchPlan {
+            removed,
+            appended,
+            original_len: second.lines.len(),
+            missing_newline: second.missing_newline,
+        })
+    }
+
+    /// Consecutive removed lines merged into ranges, ascending.
+    pub fn removed_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for &index in &self.removed {
+            match ranges.last_mut() {
+                Some(last) if last.end == index => last.end += 1,
+                _ => ranges.push(index..index + 1),
+            }
+        }
+        ranges
+    }
+
+    // Whether the second file's last line lacks a newline and is kept
+    fn keeps_unended_line(&self) -> bool {
+        self.missing_newline && self.removed.last() != Some(&(self.original_len - 1))
+    }
+
+    /// The plan as an edit script from the second file to its patched form,
+    /// for rendering as a unified diff. A last line gaining a newline is
+    /// deleted and inserted again, as `diff -u` writes it.
+    pub fn edits(&self) -> Vec<Edit> {
+        let mut edits = Vec::with_capacity(self.original_len + self.appended.len());
+        let mut removed = self.removed.iter().peekable();
+        let mut kept = 0;
+        for index in 0..self.original_len {
+            if removed.next_if_eq(&&index).is_some() {
+                edits.push(Edit::Delete(index));
+            } else {
+                edits.push(Edit::Equal(index, kept));
+                kept += 1;
+            }
+        }
+        // Lines appended after it need it ended first
+        if self.keeps_unended_line()
+            && !self.appended.is_empty()
+            && let Some(Edit::Equal(index, last)) = edits.pop()
+        {
+            edits.push(Edit::Delete(index));
+            edits.push(Edit::Insert(last));
+        }
+        edits.extend((kept..kept + self.appended.len()).map(Edit::Insert));
+        edits
+    }
+
+    /// The second file after patching, its lines indexed as the `Insert`s and
+    /// `Equal`s of [`edits`](PatchPlan::edits) expect.
+    pub fn patched(&self, second: &FileLines) -> FileLines {
+        let mut removed = self.removed.iter().peekable();
+        let mut lines = Vec::new();
+        for (index, line) in second.lines.iter().enumerate() {
+            if removed.next_if_eq(&&index).is_none() {
+                lines.push(line.clone());
+            }
+        }
+        lines.extend(self.appended.iter().cloned());
+        FileLines {
+            lines,
+            missing_newline: self.keeps_unended_line() && self.appended.is_empty(),
+        }
+    }
+}
+
+/// Every line of `filename`, empty ones included. Lines that are not UTF-8
+/// are decoded lossily, keeping line numbers.
+pub fn read_all_lines(filename: &str) -> io::Result<Vec<String>> {
+    Ok(FileLines::read(filename)?.lines)
+}
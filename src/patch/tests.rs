@@ -0,0 +1,216 @@
+#[cfg(test)]
+mod tests {
+    use crate::comparer::Comparer;
+    use crate::line_source::FileLines;
+    use crate::ordered_diff::{Edit, build_hunks};
+    use crate::patch::{PatchPlan, read_all_lines};
+    use crate::render::write_unified_diff;
+    use crate::test_util::temp_path;
+    use std::collections::BTreeSet;
+    use std::process::Command;
+    use tries::{SymbolTable, TernarySearchTrie};
+
+    fn file(lines: &[&str]) -> FileLines {
+        FileLines {
+            lines: self::lines(lines),
+            missing_newline: false,
+        }
+    }
+
+    // Emits the unified patch `--emit-patch` writes for `first` and `second`,
+    // applies it to a copy of `second` with `patch` and gives the result
+    fn emit_and_apply(name: &str, first: &str, second: &str, remove_extras: bool) -> Vec<u8> {
+        let comparer = Comparer::new(first, second);
+        let (first_table, second_table) =
+            comparer.build_tables::<TernarySearchTrie<u32>>().unwrap();
+        let result = comparer.compare_tables(&first_table, &second_table);
+        let second_file = FileLines::read(second).unwrap();
+        let plan = PatchPlan::new(
+            &read_all_lines(first).unwrap(),
+            &second_file,
+            &result.only_in_first,
+            &first_table.symbol_table,
+            false,
+            remove_extras,
+        )
+        .unwrap();
+        let hunks = build_hunks(&plan.edits(), 3);
+        let mut patch = Vec::new();
+        write_unified_diff(
+            &mut patch,
+            second,
+            second,
+            &second_file,
+            &plan.patched(&second_file),
+            &hunks,
+        )
+        .unwrap();
+
+        let copy = temp_path(&format!("{}.txt", name));
+        let patch_file = temp_path(&format!("{}.diff", name));
+        std::fs::copy(second, &copy).unwrap();
+        std::fs::write(&patch_file, patch).unwrap();
+        let status = Command::new("patch")
+            .args(["--silent", "--force", copy.as_str(), patch_file.as_str()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        std::fs::read(&copy).unwrap()
+    }
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    fn build(lines: &[String], ignore_case: bool) -> TernarySearchTrie<u32> {
+        let mut table = TernarySearchTrie::new();
+        for (index, line) in lines.iter().enumerate() {
+            let key = if ignore_case {
+                line.to_uppercase()
+            } else {
+                line.clone()
+            };
+            table.put(key, index as u32);
+        }
+        table
+    }
+
+    #[test]
+    fn test_append_only() {
+        let first = lines(&["she", "Sells", "sea"]);
+        let second = file(&["sea", "shore", "she"]);
+        let missing = [(1, "SELLS".to_string())];
+
+        let plan =
+            PatchPlan::new(&first, &second, &missing, &build(&first, true), true, false).unwrap();
+
+        assert_eq!(
+            plan,
+            PatchPlan {
+                removed: vec![],
+                appended: lines(&["Sells"]),
+                original_len: 3,
+                missing_newline: false,
+            }
+        );
+        assert_eq!(
+            plan.edits(),
+            [
+                Edit::Equal(0, 0),
+                Edit::Equal(1, 1),
+                Edit::Equal(2, 2),
+                Edit::Insert(3)
+            ]
+        );
+        assert_eq!(build_hunks(&plan.edits(), 0)[0].header(), "@@ -3,0 +4 @@");
+    }
+
+    #[test]
+    fn test_line_past_the_end() {
+        // The first file lost lines between the report and reading it again
+        let first = lines(&["she"]);
+        let second = file(&["sea"]);
+        let missing = [(4, "shore".to_string())];
+
+        let result = PatchPlan::new(
+            &first,
+            &second,
+            &missing,
+            &build(&first, false),
+            false,
+            false,
+        );
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_remove_extras() {
+        let first = lines(&["she", "sea"]);
+        let second = file(&["by", "sea", "", "the", "shore", "by"]);
+
+        let plan =
+            PatchPlan::new(&first, &second, &[], &build(&first, false), false, true).unwrap();
+
+        assert_eq!(plan.removed, [0, 3, 4, 5]);
+        assert_eq!(plan.removed_ranges(), [0..1, 3..6]);
+        assert_eq!(plan.patched(&second), file(&["sea", ""]));
+        assert_eq!(
+            plan.edits(),
+            [
+                Edit::Delete(0),
+                Edit::Equal(1, 0),
+                Edit::Equal(2, 1),
+                Edit::Delete(3),
+                Edit::Delete(4),
+                Edit::Delete(5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unended_last_line() {
+        let first = lines(&["sea", "shore"]);
+        let mut second = file(&["sea", "by"]);
+        second.missing_newline = true;
+        let missing = [(1, "shore".to_string())];
+
+        let plan = PatchPlan::new(
+            &first,
+            &second,
+            &missing,
+            &build(&first, false),
+            false,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            plan.edits(),
+            [
+                Edit::Equal(0, 0),
+                Edit::Delete(1),
+                Edit::Insert(1),
+                Edit::Insert(2)
+            ]
+        );
+        assert!(!plan.patched(&second).missing_newline);
+
+        let plan =
+            PatchPlan::new(&first, &second, &[], &build(&first, false), false, false).unwrap();
+        assert_eq!(plan.edits(), [Edit::Equal(0, 0), Edit::Equal(1, 1)]);
+        assert!(plan.patched(&second).missing_newline);
+    }
+
+    #[test]
+    fn test_patch_applies() {
+        // Turns first.txt into a file holding the lines of second.txt, the
+        // missing one appended after first.txt's unended last line
+        let patched = emit_and_apply(
+            "fixtures",
+            "src/test_data/second.txt",
+            "src/test_data/first.txt",
+            true,
+        );
+        let patched: BTreeSet<_> = String::from_utf8(patched)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        let expected: BTreeSet<_> = read_all_lines("src/test_data/second.txt")
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(patched, expected);
+    }
+
+    #[test]
+    fn test_invalid_utf8_line() {
+        let first = temp_path("invalid-first.txt");
+        let second = temp_path("invalid-second.txt");
+        std::fs::write(&first, b"sea\n\xff\xfe\nshore\n").unwrap();
+        std::fs::write(&second, "sea\n").unwrap();
+        assert_eq!(
+            emit_and_apply("invalid", first.as_str(), second.as_str(), false),
+            b"sea\nshore\n"
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Matching against a reference file of patterns, for `--patterns`.
+//!
+//! Lines of the second file without any pattern syntax are literals and go
+//! into a trie as usual, so exact matches stay a single lookup. Only the real
+//! patterns are compiled, together into one [`RegexSet`], and only lines of the
+//! first file missing from the trie are run against it.
+
+use crate::clap_parser::PatternSyntax;
+use regex::{RegexSet, RegexSetBuilder};
+use std::fmt;
+use tries::{SymbolTable, TernarySearchTrie};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+/// A line of the pattern file that failed to compile.
+#[derive(Debug)]
+pub struct PatternError {
+    pub line: u32,
+    pub error: regex::Error,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+/// Every list is in line-number order.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PatternReport {
+    /// Lines of the first file matching no literal or pattern
+    pub unmatched: Vec<(u32, String)>,
+    /// Literals and patterns of the second file matching no line of the first
+    pub unused: Vec<(u32, String)>,
+}
+
+pub struct PatternSet {
+    literals: TernarySearchTrie<u32>,
+    patterns: Vec<(u32, String)>,
+    set: RegexSet,
+    ignore_case: bool,
+}
+
+impl PatternSet {
+    pub fn new(
+        lines: &[String],
+        syntax: PatternSyntax,
+        ignore_case: bool,
+    ) -> Result<Self, PatternError> {
+        let mut literals = TernarySearchTrie::new();
+        let mut patterns = Vec::new();
+        let mut regexes = Vec::new();
+
+        for (index, line) in lines.iter().enumerate() {
+            let regex = match syntax {
+                PatternSyntax::Glob if is_glob(line) => glob_to_regex(line),
+                PatternSyntax::Regex if regex::escape(line) != *line => format!("^(?:{})$", line),
+                _ => {
+                    let key = if ignore_case {
+                        line.to_uppercase()
+                    } else {
+                        line.clone()
+                    };
+                    literals.put(key, index as u32);
+                    continue;
+                }
+            };
+            // Compiled one at a time first, so an error names its line
+            RegexSetBuilder::new([&regex])
+                .build()
+                .map_err(|error| PatternError {
+                    line: index as u32,
+                    error,
+                })?;
+            patterns.push((index as u32, line.clone()));
+            regexes.push(regex);
+        }
+
+        let set = RegexSetBuilder::new(&regexes)
+            .case_insensitive(ignore_case)
+            .build()
+            .map_err(|error| PatternError {
+                line: patterns.first().map_or(0, |p| p.0),
+                error,
+            })?;
+
+        Ok(PatternSet {
+            literals,
+            patterns,
+            set,
+            ignore_case,
+        })
+    }
+
+    /// Checks every line of `symbol_table_1`, as built by
+    /// `build_symbol_table` with the same `ignore_case`.
+    pub fn match_lines<T: SymbolTable<u32>>(&self, symbol_table_1: &T) -> PatternReport {
+        let mut report = PatternReport::default();
+        let mut used_literals = TernarySearchTrie::<bool>::new();
+        let mut used_patterns = vec![false; self.patterns.len()];
+
+        for key in symbol_table_1.get_all_keys() {
+            if self.literals.contains(&key) {
+                used_literals.put(key, true);
+                continue;
+            }
+            let matches = self.set.matches(&key);
+            if !matches.matched_any() {
+                let line = symbol_table_1.get(&key).unwrap();
+                report.unmatched.push((line, key));
+                continue;
+            }
+            for i in matches.iter() {
+                used_patterns[i] = true;
+            }
+        }
+
+        for key in self.literals.get_all_keys() {
+            if !used_literals.contains(&key) {
+                report.unused.push((self.literals.get(&key).unwrap(), key));
+            }
+        }
+        for (pattern, used) in self.patterns.iter().zip(used_patterns) {
+            if !used {
+                let text = if self.ignore_case {
+                    pattern.1.to_uppercase()
+                } else {
+                    pattern.1.clone()
+                };
+                report.unused.push((pattern.0, text));
+            }
+        }
+
+        report.unmatched.sort_by_key(|k| k.0);
+        report.unused.sort_by_key(|k| k.0);
+        report
+    }
+}
+
+fn is_glob(line: &str) -> bool {
+    line.contains(['*', '?', '['])
+}
+
+/// Translates a glob to an anchored regex. `*` and `?` stay within one path
+/// segment, `**` crosses them, and `[...]` or `[!...]` is a character class.
+pub fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                let class: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                let class = match class.strip_prefix('!') {
+                    Some(negated) => format!("^{}", negated),
+                    None => class,
+                };
+                regex.push('[');
+                regex.push_str(&class.replace('\\', "\\\\").replace('[', "\\["));
+                regex.push(']');
+            }
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    regex.push('$');
+    regex
+}
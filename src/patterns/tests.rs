@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use crate::clap_parser::PatternSyntax;
+    use crate::patterns::{PatternSet, glob_to_regex};
+    use regex::Regex;
+    use tries::{SymbolTable, TernarySearchTrie};
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    fn build(lines: &[&str], ignore_case: bool) -> TernarySearchTrie<u32> {
+        let mut table = TernarySearchTrie::new();
+        for (index, line) in lines.iter().enumerate() {
+            let key = if ignore_case {
+                line.to_uppercase()
+            } else {
+                line.to_string()
+            };
+            table.put(key, index as u32);
+        }
+        table
+    }
+
+    fn glob_matches(glob: &str, text: &str) -> bool {
+        Regex::new(&glob_to_regex(glob)).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn test_glob() {
+        assert!(glob_matches("user_*", "user_123"));
+        assert!(!glob_matches("user_*", "admin_user_1"));
+        assert!(glob_matches("192.168.?.1", "192.168.0.1"));
+        assert!(!glob_matches("192.168.?.1", "192.168.10.1"));
+        assert!(!glob_matches("192.168.?.1", "192x168.0.1"));
+        assert!(glob_matches("**/*.log", "app.log"));
+        assert!(glob_matches("**/*.log", "var/log/app.log"));
+        assert!(!glob_matches("*.log", "var/app.log"));
+        assert!(glob_matches("file[0-9]", "file7"));
+        assert!(glob_matches("file[!0-9]", "fileX"));
+        assert!(!glob_matches("file[!0-9]", "file7"));
+    }
+
+    #[test]
+    fn test_glob_report() {
+        let first = build(
+            &["user_123", "root", "192.168.0.1", "var/log/app.log"],
+            false,
+        );
+        let set = PatternSet::new(
+            &lines(&["user_*", "root", "admin", "10.*", "192.168.?.1", "**/*.log"]),
+            PatternSyntax::Glob,
+            false,
+        )
+        .unwrap();
+
+        let report = set.match_lines(&first);
+
+        assert!(report.unmatched.is_empty());
+        assert_eq!(
+            report.unused,
+            [(2, "admin".to_string()), (3, "10.*".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_regex_report() {
+        let first = build(&["User_123", "user_abc", "root"], true);
+        let set = PatternSet::new(
+            &lines(&[r"user_\d+", "ROOT", "nobody"]),
+            PatternSyntax::Regex,
+            true,
+        )
+        .unwrap();
+
+        let report = set.match_lines(&first);
+
+        assert_eq!(report.unmatched, [(1, "USER_ABC".to_string())]);
+        assert_eq!(report.unused, [(2, "NOBODY".to_string())]);
+    }
+
+    #[test]
+    fn test_invalid_pattern_names_its_line() {
+        let error = PatternSet::new(&lines(&["ok", "(unclosed"]), PatternSyntax::Regex, false)
+            .err()
+            .unwrap();
+        assert_eq!(error.line, 1);
+    }
+}
@@ -0,0 +1,87 @@
+//! Grouping difference lists by common prefix for `--group-by-prefix`.
+//!
+//! The differences go into a trie, and the tree is discovered top-down: at
+//! each level, [`get_keys_with_prefix`](PrefixSearch::get_keys_with_prefix)
+//! lists the keys under the current prefix, and each key is cut at its next
+//! separator to find the child groups. A group holding nothing but a single
+//! subgroup is folded into it, so `usr/local/bin/` shows as one level rather
+//! than three.
+
+use tries::{PrefixSearch, SymbolTable, TernarySearchTrie};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+/// A namespace in the tree: every difference starting with `prefix`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PrefixGroup {
+    /// The part of `prefix` beyond the parent group's
+    pub label: String,
+    pub prefix: String,
+    /// Differences anywhere below this group
+    pub count: usize,
+    pub groups: Vec<PrefixGroup>,
+    /// Differences directly in this group, as line number and full text
+    pub lines: Vec<(u32, String)>,
+}
+
+/// Builds the tree for one difference list. `separators` are the characters
+/// ending a path segment or namespace, such as `/` or `.`.
+pub fn group_by_prefix(differences: &[(u32, String)], separators: &str) -> PrefixGroup {
+    let mut symbol_table = TernarySearchTrie::<u32>::new();
+    for (num, text) in differences {
+        symbol_table.put(text.clone(), *num);
+    }
+    build_group(&symbol_table, String::new(), String::new(), separators)
+}
+
+fn build_group(
+    symbol_table: &TernarySearchTrie<u32>,
+    label: String,
+    prefix: String,
+    separators: &str,
+) -> PrefixGroup {
+    let keys = symbol_table.get_keys_with_prefix(&prefix);
+    let mut group = PrefixGroup {
+        label,
+        prefix: prefix.clone(),
+        count: keys.len(),
+        groups: Vec::new(),
+        lines: Vec::new(),
+    };
+
+    // Keys come back sorted, so keys sharing a child prefix are adjacent
+    let mut child_prefixes: Vec<String> = Vec::new();
+    for key in keys {
+        let rest = &key[prefix.len()..];
+        match rest.find(|c: char| separators.contains(c)) {
+            // A key ending in a separator names the group itself, and is
+            // listed inside it
+            Some(at) => {
+                let separator_len = rest[at..].chars().next().unwrap().len_utf8();
+                let child = key[..prefix.len() + at + separator_len].to_string();
+                if child_prefixes.last() != Some(&child) {
+                    child_prefixes.push(child);
+                }
+            }
+            None => {
+                let num = symbol_table.get(&key).unwrap();
+                group.lines.push((num, key));
+            }
+        }
+    }
+
+    for child_prefix in child_prefixes {
+        let child_label = child_prefix[prefix.len()..].to_string();
+        let mut child = build_group(symbol_table, child_label, child_prefix, separators);
+        if child.lines.is_empty() && child.groups.len() == 1 {
+            let mut only = child.groups.pop().unwrap();
+            only.label = child.label + &only.label;
+            child = only;
+        }
+        group.groups.push(child);
+    }
+
+    group.lines.sort_by_key(|k| k.0);
+    group
+}
@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use crate::prefix_groups::{PrefixGroup, group_by_prefix};
+    use crate::render::{render_html_grouped_output, render_text_grouped_output};
+    use crate::summary::Summary;
+
+    fn lines(lines: &[&str]) -> Vec<(u32, String)> {
+        lines
+            .iter()
+            .enumerate()
+            .map(|(num, text)| (num as u32, text.to_string()))
+            .collect()
+    }
+
+    // The tree as indented "label (count)" and leaf lines, for compact
+    // comparison
+    fn outline(group: &PrefixGroup, depth: usize, out: &mut Vec<String>) {
+        for child in &group.groups {
+            out.push(format!(
+                "{}{} ({})",
+                "  ".repeat(depth),
+                child.label,
+                child.count
+            ));
+            outline(child, depth + 1, out);
+        }
+        for (num, text) in &group.lines {
+            out.push(format!("{}{}: {}", "  ".repeat(depth), num, text));
+        }
+    }
+
+    #[test]
+    fn test_paths() {
+        let root = group_by_prefix(
+            &lines(&[
+                "src/main.rs",
+                "src/render/mod.rs",
+                "src/render/tests.rs",
+                "usr/local/bin/tool",
+                "README.md",
+            ]),
+            "/",
+        );
+
+        let mut out = Vec::new();
+        outline(&root, 0, &mut out);
+        assert_eq!(
+            out,
+            [
+                "src/ (3)",
+                "  render/ (2)",
+                "    1: src/render/mod.rs",
+                "    2: src/render/tests.rs",
+                "  0: src/main.rs",
+                "usr/local/bin/ (1)",
+                "  3: usr/local/bin/tool",
+                "4: README.md",
+            ]
+        );
+        assert_eq!(root.count, 5);
+    }
+
+    #[test]
+    fn test_dotted_keys() {
+        let root = group_by_prefix(&lines(&["db.host", "db.port", "db.", "cache.ttl"]), ".");
+
+        let mut out = Vec::new();
+        outline(&root, 0, &mut out);
+        assert_eq!(
+            out,
+            [
+                "cache. (1)",
+                "  3: cache.ttl",
+                "db. (3)",
+                "  0: db.host",
+                "  1: db.port",
+                "  2: db.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_grouped_reports_start_with_summary() {
+        let first = group_by_prefix(&lines(&["db.host", "db.port"]), ".");
+        let second = group_by_prefix(&lines(&["cache.ttl"]), ".");
+        let summary = Summary {
+            first_total: 4,
+            first_distinct: 3,
+            second_total: 2,
+            second_distinct: 2,
+            common: 1,
+        };
+
+        let mut text = Vec::new();
+        render_text_grouped_output(&mut text, "a", "b", summary, &first, &second).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        let heading = text.find("SUMMARY OF FIRST (a) AND SECOND (b)").unwrap();
+        for row in [
+            "FIRST: 4 lines, 3 distinct, 1 duplicates\n",
+            "SECOND: 2 lines, 2 distinct, 0 duplicates\n",
+            "COMMON: 1 distinct lines\n",
+            "JACCARD SIMILARITY: 0.2500\n",
+        ] {
+            assert!(text[heading..].contains(row), "{}", row);
+        }
+        assert!(heading < text.find("LINES IN FIRST (a)").unwrap());
+        assert!(text.contains("db. (2)\n  line 0: db.host\n  line 1: db.port\n"));
+
+        let mut html = Vec::new();
+        render_html_grouped_output(&mut html, "a", "b", summary, &first, &second).unwrap();
+        let html = String::from_utf8(html).unwrap();
+        let heading = html.find("SUMMARY OF FIRST").unwrap();
+        assert!(html.contains("<td>JACCARD SIMILARITY</td><td>0.2500</td>"));
+        assert!(heading < html.find("LINES IN FIRST").unwrap());
+    }
+}
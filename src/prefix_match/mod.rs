@@ -0,0 +1,87 @@
+//! Prefix coverage for `--match prefix`: the second file is a list of rules
+//! such as URL, path or route prefixes, and a line of the first file is
+//! covered when some rule is a prefix of it.
+//!
+//! Each line is resolved with one
+//! [`longest_prefix_of`](PrefixSearch::longest_prefix_of) walk of the rules'
+//! trie, which also picks the most specific rule when several apply. A rule
+//! only counts as unused when no line starts with it, even if more specific
+//! rules cover every line it would.
+
+use tries::{PrefixSearch, SymbolTable, TernarySearchTrie};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+/// A line of the first file and the rule of the second that covers it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoveredLine {
+    pub line: u32,
+    pub text: String,
+    pub rule_line: u32,
+    pub rule: String,
+}
+
+/// Every list is in line-number order.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Coverage {
+    pub covered: Vec<CoveredLine>,
+    pub uncovered: Vec<(u32, String)>,
+    pub unused_rules: Vec<(u32, String)>,
+}
+
+// Marks `rule` and every shorter rule it starts with as used. The shorter
+// ones were all marked along with any rule already used, so the walk stops
+// there.
+fn mark_used<T: PrefixSearch>(rules: &T, used_rules: &mut TernarySearchTrie<bool>, rule: &str) {
+    let mut next = Some(rule.to_string());
+    while let Some(rule) = next {
+        if used_rules.contains(&rule) {
+            break;
+        }
+        next = rule
+            .char_indices()
+            .last()
+            .filter(|&(last, _)| last > 0)
+            .and_then(|(last, _)| rules.longest_prefix_of(&rule[..last]));
+        used_rules.put(rule, true);
+    }
+}
+
+/// Checks every line of `symbol_table_1` against the rules in
+/// `symbol_table_2`, both as built by `build_symbol_table`.
+pub fn match_prefixes<T: SymbolTable<u32> + PrefixSearch>(
+    symbol_table_1: &T,
+    symbol_table_2: &T,
+) -> Coverage {
+    let mut coverage = Coverage::default();
+    let mut used_rules = TernarySearchTrie::<bool>::new();
+
+    for key in symbol_table_1.get_all_keys() {
+        let line = symbol_table_1.get(&key).unwrap();
+        match symbol_table_2.longest_prefix_of(&key) {
+            Some(rule) => {
+                mark_used(symbol_table_2, &mut used_rules, &rule);
+                coverage.covered.push(CoveredLine {
+                    line,
+                    text: key,
+                    rule_line: symbol_table_2.get(&rule).unwrap(),
+                    rule,
+                });
+            }
+            None => coverage.uncovered.push((line, key)),
+        }
+    }
+
+    for rule in symbol_table_2.get_all_keys() {
+        if !used_rules.contains(&rule) {
+            let rule_line = symbol_table_2.get(&rule).unwrap();
+            coverage.unused_rules.push((rule_line, rule));
+        }
+    }
+
+    coverage.covered.sort_by_key(|covered| covered.line);
+    coverage.uncovered.sort_by_key(|k| k.0);
+    coverage.unused_rules.sort_by_key(|k| k.0);
+    coverage
+}
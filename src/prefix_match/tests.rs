@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use crate::prefix_match::{CoveredLine, match_prefixes};
+    use tries::{SymbolTable, TernarySearchTrie};
+
+    fn build(lines: &[&str]) -> TernarySearchTrie<u32> {
+        let mut table = TernarySearchTrie::new();
+        for (index, line) in lines.iter().enumerate() {
+            table.put(line.to_string(), index as u32);
+        }
+        table
+    }
+
+    #[test]
+    fn test_coverage() {
+        let urls = build(&[
+            "https://example.com/api/users",
+            "https://example.com/static/app.js",
+            "https://other.org/",
+        ]);
+        let rules = build(&["https://example.com/", "https://example.com/api/", "ftp://"]);
+
+        let coverage = match_prefixes(&urls, &rules);
+
+        assert_eq!(
+            coverage.covered,
+            [
+                CoveredLine {
+                    line: 0,
+                    text: "https://example.com/api/users".to_string(),
+                    rule_line: 1,
+                    rule: "https://example.com/api/".to_string(),
+                },
+                CoveredLine {
+                    line: 1,
+                    text: "https://example.com/static/app.js".to_string(),
+                    rule_line: 0,
+                    rule: "https://example.com/".to_string(),
+                },
+            ]
+        );
+        assert_eq!(coverage.uncovered, [(2, "https://other.org/".to_string())]);
+        assert_eq!(coverage.unused_rules, [(2, "ftp://".to_string())]);
+    }
+
+    #[test]
+    fn test_exact_line_is_its_own_prefix() {
+        let coverage = match_prefixes(&build(&["10.0.0.1"]), &build(&["10.0.0.1"]));
+        assert_eq!(coverage.covered.len(), 1);
+        assert!(coverage.unused_rules.is_empty());
+    }
+
+    #[test]
+    fn test_shorter_rules_are_used() {
+        let paths = build(&["/usr/local/bin/rg", "/usr/lib/libc.so"]);
+        let rules = build(&["/", "/usr/", "/usr/local/", "/usr/local/bin/", "/opt/"]);
+
+        let coverage = match_prefixes(&paths, &rules);
+        assert_eq!(coverage.covered[0].rule, "/usr/local/bin/");
+        assert_eq!(coverage.covered[1].rule, "/usr/");
+        assert_eq!(coverage.unused_rules, [(4, "/opt/".to_string())]);
+    }
+}
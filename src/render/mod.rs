@@ -0,0 +1,1449 @@
+use crate::approximate::Triage;
+use crate::comparer::ComparisonResult;
+use crate::directory::{DirectoryComparison, FileComparison};
+use crate::expr::Source;
+use crate::fuzzy::{highlight, highlight_html, highlight_text};
+use crate::history::{LineHistory, Timeline};
+use crate::line_source::FileLines;
+use crate::ordered_diff::{Edit, Hunk};
+use crate::patch::PatchPlan;
+use crate::patterns::PatternReport;
+use crate::prefix_groups::PrefixGroup;
+use crate::prefix_match::Coverage;
+use crate::snapshot::SnapshotHeader;
+use crate::stream::{Side, StreamRenderer};
+use crate::summary::Summary;
+use crate::three_way::{Class, ThreeWayLine, ThreeWayReport};
+use crate::tokenizer::Occurrence;
+use crate::watch::Delta;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+fn build_separator() -> String {
+    let template = "*";
+    let n = 80;
+    template.repeat(n)
+}
+
+pub fn print_separator() {
+    println!("{}", build_separator());
+}
+
+pub fn print_html_header() {
+    write_html_header(&mut io::stdout()).expect("failed printing to stdout");
+}
+
+pub fn print_html_footer() {
+    write_html_footer(&mut io::stdout()).expect("failed printing to stdout");
+}
+
+fn write_separator<W: Write + ?Sized>(out: &mut W) -> io::Result<()> {
+    writeln!(out, "{}", build_separator())
+}
+
+fn write_html_header<W: Write + ?Sized>(out: &mut W) -> io::Result<()> {
+    writeln!(out, "<html>")?;
+    writeln!(out, "<head>")?;
+    writeln!(out, "<style>")?;
+    writeln!(out, ".table-section {{ background-color: #A6AEBF;  }} ")?;
+    writeln!(out, ".table-header {{ background-color: #C5D3E8; }} ")?;
+    writeln!(out, ".table-body {{ background-color: #D0E8C5; }} ")?;
+    writeln!(out, ".table-footer {{ background-color: #FFF8DE; }} ")?;
+    writeln!(out, "</style>")?;
+    writeln!(out, "</head>")?;
+    writeln!(out, "<body>")
+}
+
+fn write_html_footer<W: Write + ?Sized>(out: &mut W) -> io::Result<()> {
+    writeln!(out, "</body></html>")
+}
+
+/// A format a [`ComparisonResult`] can be written in.
+pub trait Renderer {
+    fn render(&self, result: &ComparisonResult, out: &mut dyn Write) -> io::Result<()>;
+}
+
+/// The plain-text report.
+pub struct TextRenderer;
+
+/// The report as an HTML table.
+pub struct HtmlRenderer;
+
+impl Renderer for TextRenderer {
+    fn render(&self, result: &ComparisonResult, out: &mut dyn Write) -> io::Result<()> {
+        let (first, second) = (&result.first, &result.second);
+        let mut stream = TextStream::new(&mut *out, first, second).with_summary(result.summary());
+        write_sides(&mut stream, result)?;
+        stream.finish()?;
+        if let Some(moved_lines) = &result.moved {
+            writeln!(
+                out,
+                "LINES MOVED BETWEEN FIRST ({}) AND SECOND ({})",
+                first, second
+            )?;
+            write_separator(out)?;
+            for moved in moved_lines {
+                writeln!(
+                    out,
+                    "line {} -> line {}: {}",
+                    moved.old_line, moved.new_line, moved.text
+                )?;
+            }
+            write_separator(out)?;
+            writeln!(out, "TOTAL: {}", moved_lines.len())?;
+            write_separator(out)?;
+        }
+        if let Some(changed_lines) = &result.changed {
+            writeln!(
+                out,
+                "LINES CHANGED BETWEEN FIRST ({}) AND SECOND ({})",
+                first, second
+            )?;
+            write_separator(out)?;
+            for changed in changed_lines {
+                writeln!(
+                    out,
+                    "line {} -> line {}: {}",
+                    changed.old_line,
+                    changed.new_line,
+                    highlight_text(&highlight(&changed.old_text, &changed.new_text))
+                )?;
+            }
+            write_separator(out)?;
+            writeln!(out, "TOTAL: {}", changed_lines.len())?;
+            write_separator(out)?;
+        }
+        Ok(())
+    }
+}
+
+impl Renderer for HtmlRenderer {
+    fn render(&self, result: &ComparisonResult, out: &mut dyn Write) -> io::Result<()> {
+        let (first, second) = (&result.first, &result.second);
+        let mut stream = HtmlStream::new(&mut *out, first, second).with_summary(result.summary());
+        write_sides(&mut stream, result)?;
+        if let Some(moved_lines) = &result.moved {
+            let out = stream.writer();
+            writeln!(
+                out,
+                "<tr class=table-section><td colspan=2>LINES MOVED BETWEEN FIRST (<b>{}</b>) AND SECOND (<b>{}</b>)</td></tr>",
+                first, second
+            )?;
+            writeln!(
+                out,
+                "<tr class=table-header><th>Line Numbers</th><th>Text</th></tr>"
+            )?;
+            for moved in moved_lines {
+                writeln!(
+                    out,
+                    "<tr class=table-body><td>{} &rarr; {}</td><td>{}</td></tr>",
+                    moved.old_line, moved.new_line, moved.text
+                )?;
+            }
+            writeln!(
+                out,
+                "<tr class=table-footer><td colspan=2>TOTAL: {}</td></tr>",
+                moved_lines.len()
+            )?;
+        }
+        if let Some(changed_lines) = &result.changed {
+            let out = stream.writer();
+            writeln!(
+                out,
+                "<tr class=table-section><td colspan=2>LINES CHANGED BETWEEN FIRST (<b>{}</b>) AND SECOND (<b>{}</b>)</td></tr>",
+                first, second
+            )?;
+            writeln!(
+                out,
+                "<tr class=table-header><th>Line Numbers</th><th>Text</th></tr>"
+            )?;
+            for changed in changed_lines {
+                writeln!(
+                    out,
+                    "<tr class=table-body><td>{} &rarr; {}</td><td>{}</td></tr>",
+                    changed.old_line,
+                    changed.new_line,
+                    highlight_html(&highlight(&changed.old_text, &changed.new_text))
+                )?;
+            }
+            writeln!(
+                out,
+                "<tr class=table-footer><td colspan=2>TOTAL: {}</td></tr>",
+                changed_lines.len()
+            )?;
+        }
+        stream.finish()
+    }
+}
+
+// Both lists of differences of a batch result, through the streaming form
+fn write_sides(stream: &mut dyn StreamRenderer, result: &ComparisonResult) -> io::Result<()> {
+    stream.start()?;
+    for (side, lines) in [
+        (Side::FirstOnly, &result.only_in_first),
+        (Side::SecondOnly, &result.only_in_second),
+    ] {
+        stream.start_side(side)?;
+        for (num, text) in lines {
+            stream.line(*num, text)?;
+        }
+        stream.end_side(lines.len(), false)?;
+    }
+    Ok(())
+}
+
+// Label and value of each line of the summary block
+fn summary_rows(summary: &Summary) -> [(&'static str, String); 6] {
+    [
+        (
+            "FIRST",
+            format!(
+                "{} lines, {} distinct, {} duplicates",
+                summary.first_total,
+                summary.first_distinct,
+                summary.first_duplicates()
+            ),
+        ),
+        (
+            "SECOND",
+            format!(
+                "{} lines, {} distinct, {} duplicates",
+                summary.second_total,
+                summary.second_distinct,
+                summary.second_duplicates()
+            ),
+        ),
+        ("COMMON", format!("{} distinct lines", summary.common)),
+        ("JACCARD SIMILARITY", format!("{:.4}", summary.jaccard())),
+        (
+            "FIRST CONTAINED IN SECOND",
+            format!("{:.4}", summary.first_in_second()),
+        ),
+        (
+            "SECOND CONTAINED IN FIRST",
+            format!("{:.4}", summary.second_in_first()),
+        ),
+    ]
+}
+
+fn truncation_note(truncated: bool) -> &'static str {
+    if truncated {
+        " (limit reached, more not shown)"
+    } else {
+        ""
+    }
+}
+
+/// The heading of a `--watch` redraw: how each list of differences changed
+/// since the previous run.
+pub fn write_text_delta(
+    out: &mut dyn Write,
+    run: usize,
+    first: &str,
+    second: &str,
+    deltas: Option<(Delta, Delta)>,
+) -> io::Result<()> {
+    write_separator(out)?;
+    writeln!(out, "WATCHING FIRST ({}) AND SECOND ({}), RUN {}", first, second, run)?;
+    let Some((first_delta, second_delta)) = deltas else {
+        return Ok(());
+    };
+    write_separator(out)?;
+    for (heading, delta) in [
+        ("FIRST", first_delta),
+        ("SECOND", second_delta),
+    ] {
+        writeln!(
+            out,
+            "{} SINCE PREVIOUS RUN: +{} new missing lines, -{} no longer missing",
+            heading, delta.added, delta.removed
+        )?;
+    }
+    Ok(())
+}
+
+/// The plain-text report, written as the lines arrive.
+pub struct TextStream<W: Write> {
+    out: W,
+    first: String,
+    second: String,
+    summary: Option<Summary>,
+}
+
+impl<W: Write> TextStream<W> {
+    pub fn new(out: W, first: &str, second: &str) -> Self {
+        TextStream {
+            out,
+            first: first.to_string(),
+            second: second.to_string(),
+            summary: None,
+        }
+    }
+
+    /// Heads the report with `summary`.
+    pub fn with_summary(mut self, summary: Summary) -> Self {
+        self.summary = Some(summary);
+        self
+    }
+
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.out
+    }
+}
+
+impl<W: Write> StreamRenderer for TextStream<W> {
+    fn start(&mut self) -> io::Result<()> {
+        write_separator(&mut self.out)?;
+        let Some(summary) = &self.summary else {
+            return Ok(());
+        };
+        writeln!(
+            self.out,
+            "SUMMARY OF FIRST ({}) AND SECOND ({})",
+            self.first, self.second
+        )?;
+        write_separator(&mut self.out)?;
+        for (label, value) in summary_rows(summary) {
+            writeln!(self.out, "{}: {}", label, value)?;
+        }
+        write_separator(&mut self.out)
+    }
+
+    fn start_side(&mut self, side: Side) -> io::Result<()> {
+        let (this, other) = match side {
+            Side::FirstOnly => (("FIRST", &self.first), ("SECOND", &self.second)),
+            Side::SecondOnly => (("SECOND", &self.second), ("FIRST", &self.first)),
+        };
+        writeln!(
+            self.out,
+            "LINES IN {} ({}) FILE, BUT NOT IN {} ({})",
+            this.0, this.1, other.0, other.1
+        )?;
+        write_separator(&mut self.out)
+    }
+
+    fn line(&mut self, num: u32, text: &str) -> io::Result<()> {
+        writeln!(self.out, "line {}: {}", num, text)
+    }
+
+    fn end_side(&mut self, shown: usize, truncated: bool) -> io::Result<()> {
+        write_separator(&mut self.out)?;
+        writeln!(self.out, "TOTAL: {}{}", shown, truncation_note(truncated))?;
+        write_separator(&mut self.out)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// The report as an HTML table, written as the lines arrive.
+pub struct HtmlStream<W: Write> {
+    out: W,
+    first: String,
+    second: String,
+    summary: Option<Summary>,
+}
+
+impl<W: Write> HtmlStream<W> {
+    pub fn new(out: W, first: &str, second: &str) -> Self {
+        HtmlStream {
+            out,
+            first: first.to_string(),
+            second: second.to_string(),
+            summary: None,
+        }
+    }
+
+    /// Heads the report with `summary`.
+    pub fn with_summary(mut self, summary: Summary) -> Self {
+        self.summary = Some(summary);
+        self
+    }
+
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.out
+    }
+}
+
+impl<W: Write> StreamRenderer for HtmlStream<W> {
+    fn start(&mut self) -> io::Result<()> {
+        write_html_header(&mut self.out)?;
+        writeln!(self.out, "<table border=\"1\">")?;
+        let Some(summary) = &self.summary else {
+            return Ok(());
+        };
+        writeln!(
+            self.out,
+            "<tr class=table-section><td colspan=2>SUMMARY OF FIRST (<b>{}</b>) AND SECOND (<b>{}</b>)</td></tr>",
+            self.first, self.second
+        )?;
+        for (label, value) in summary_rows(summary) {
+            writeln!(
+                self.out,
+                "<tr class=table-body><td>{}</td><td>{}</td></tr>",
+                label, value
+            )?;
+        }
+        Ok(())
+    }
+
+    fn start_side(&mut self, side: Side) -> io::Result<()> {
+        let (this, other) = match side {
+            Side::FirstOnly => (("FIRST", &self.first), ("SECOND", &self.second)),
+            Side::SecondOnly => (("SECOND", &self.second), ("FIRST", &self.first)),
+        };
+        writeln!(
+            self.out,
+            "<tr class=table-section><td colspan=2>LINES IN {} (<b>{}</b>) FILE, BUT NOT IN {} (<b>{}</b>)</td></tr>",
+            this.0, this.1, other.0, other.1
+        )?;
+        writeln!(
+            self.out,
+            "<tr class=table-header><th>Line Number</th><th>Text</th></tr>"
+        )
+    }
+
+    fn line(&mut self, num: u32, text: &str) -> io::Result<()> {
+        writeln!(
+            self.out,
+            "<tr class=table-body><td>{}</td><td>{}</td></tr>",
+            num, text
+        )
+    }
+
+    fn end_side(&mut self, shown: usize, truncated: bool) -> io::Result<()> {
+        writeln!(
+            self.out,
+            "<tr class=table-footer><td colspan=2>TOTAL: {}{}</td></tr>",
+            shown,
+            truncation_note(truncated)
+        )
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        writeln!(self.out, "</table>")?;
+        write_html_footer(&mut self.out)?;
+        self.out.flush()
+    }
+}
+
+pub fn render_text_token_output(
+    out: &mut dyn Write,
+    first: &str,
+    second: &str,
+    tokens_in_first_not_in_second: &[(Occurrence, String)],
+    tokens_in_second_not_in_first: &[(Occurrence, String)],
+) -> io::Result<()> {
+    write_separator(out)?;
+    writeln!(
+        out,
+        "TOKENS IN FIRST ({}) FILE, BUT NOT IN SECOND ({})",
+        first, second
+    )?;
+    write_separator(out)?;
+    for (occurrence, text) in tokens_in_first_not_in_second {
+        writeln!(
+            out,
+            "line {}:{}: {} (x{})",
+            occurrence.line, occurrence.column, text, occurrence.count
+        )?;
+    }
+    write_separator(out)?;
+    writeln!(out, "TOTAL: {}", tokens_in_first_not_in_second.len())?;
+    write_separator(out)?;
+    writeln!(
+        out,
+        "TOKENS IN SECOND ({}) FILE, BUT NOT IN FIRST ({})",
+        second, first
+    )?;
+    write_separator(out)?;
+    for (occurrence, text) in tokens_in_second_not_in_first {
+        writeln!(
+            out,
+            "line {}:{}: {} (x{})",
+            occurrence.line, occurrence.column, text, occurrence.count
+        )?;
+    }
+    write_separator(out)?;
+    writeln!(out, "TOTAL: {}", tokens_in_second_not_in_first.len())?;
+    write_separator(out)
+}
+
+pub fn render_html_token_output(
+    out: &mut dyn Write,
+    first: &str,
+    second: &str,
+    tokens_in_first_not_in_second: &[(Occurrence, String)],
+    tokens_in_second_not_in_first: &[(Occurrence, String)],
+) -> io::Result<()> {
+    write_html_header(out)?;
+    writeln!(out, "<table border=\"1\">")?;
+    writeln!(
+        out,
+        "<tr class=table-section><td colspan=3>TOKENS IN FIRST (<b>{}</b>) FILE, BUT NOT IN SECOND (<b>{}</b>)</td></tr>",
+        first, second
+    )?;
+    writeln!(
+        out,
+        "<tr class=table-header><th>Position</th><th>Token</th><th>Count</th></tr>"
+    )?;
+    for (occurrence, text) in tokens_in_first_not_in_second {
+        writeln!(
+            out,
+            "<tr class=table-body><td>{}:{}</td><td>{}</td><td>{}</td></tr>",
+            occurrence.line, occurrence.column, text, occurrence.count
+        )?;
+    }
+    writeln!(
+        out,
+        "<tr class=table-footer><td colspan=3>TOTAL: {}</td></tr>",
+        tokens_in_first_not_in_second.len()
+    )?;
+    writeln!(
+        out,
+        "<tr class=table-section><td colspan=3>TOKENS IN SECOND (<b>{}</b>) FILE, BUT NOT IN FIRST (<b>{}</b>)</td></tr>",
+        second, first
+    )?;
+    writeln!(
+        out,
+        "<tr class=table-header><th>Position</th><th>Token</th><th>Count</th></tr>"
+    )?;
+    for (occurrence, text) in tokens_in_second_not_in_first {
+        writeln!(
+            out,
+            "<tr class=table-body><td>{}:{}</td><td>{}</td><td>{}</td></tr>",
+            occurrence.line, occurrence.column, text, occurrence.count
+        )?;
+    }
+    writeln!(
+        out,
+        "<tr class=table-footer><td colspan=3>TOTAL: {}</td></tr>",
+        tokens_in_second_not_in_first.len()
+    )?;
+    writeln!(out, "</table>")?;
+    write_html_footer(out)
+}
+
+/// Writes `hunks` as a unified diff that `patch` can apply, marking a last
+/// line without a newline as `diff -u` does.
+pub fn write_unified_diff(
+    out: &mut dyn Write,
+    first: &str,
+    second: &str,
+    old: &FileLines,
+    new: &FileLines,
+    hunks: &[Hunk],
+) -> io::Result<()> {
+    if hunks.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "--- {}", first)?;
+    writeln!(out, "+++ {}", second)?;
+    for hunk in hunks {
+        writeln!(out, "{}", hunk.header())?;
+        for edit in &hunk.edits {
+            let (prefix, file, index) = match *edit {
+                Edit::Equal(o, _) => (' ', old, o),
+                Edit::Delete(o) => ('-', old, o),
+                Edit::Insert(n) => ('+', new, n),
+            };
+            writeln!(out, "{}{}", prefix, file.lines[index])?;
+            if file.lacks_newline(index) {
+                writeln!(out, "\\ No newline at end of file")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn render_html_unified_diff(
+    first: &str,
+    second: &str,
+    old: &FileLines,
+    new: &FileLines,
+    hunks: &[Hunk],
+) {
+    print_html_header();
+    println!("<style>");
+    println!(".diff-delete {{ background-color: #F8D7DA; }} ");
+    println!(".diff-insert {{ background-color: #D0E8C5; }} ");
+    println!("</style>");
+    println!("<table border=\"1\">");
+    println!(
+        "<tr class=table-section><td colspan=3>ORDERED DIFF OF FIRST (<b>{}</b>) AND SECOND (<b>{}</b>) FILES</td></tr>",
+        first, second
+    );
+    println!("<tr class=table-header><th>First</th><th>Second</th><th>Text</th></tr>");
+    for hunk in hunks {
+        println!(
+            "<tr class=table-section><td colspan=3>{}</td></tr>",
+            hunk.header()
+        );
+        for edit in &hunk.edits {
+            match *edit {
+                Edit::Equal(o, n) => println!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    o + 1,
+                    n + 1,
+                    old.lines[o]
+                ),
+                Edit::Delete(o) => println!(
+                    "<tr class=diff-delete><td>{}</td><td></td><td>{}</td></tr>",
+                    o + 1,
+                    old.lines[o]
+                ),
+                Edit::Insert(n) => println!(
+                    "<tr class=diff-insert><td></td><td>{}</td><td>{}</td></tr>",
+                    n + 1,
+                    new.lines[n]
+                ),
+            }
+        }
+    }
+    println!(
+        "<tr class=table-footer><td colspan=3>HUNKS: {}</td></tr>",
+        hunks.len()
+    );
+    println!("</table>");
+    print_html_footer();
+}
+
+/// Writes a patch script for `ed` in the style of `diff -e`: commands run
+/// from the bottom of the file up, so earlier line numbers stay valid.
+pub fn render_ed_script(out: &mut dyn Write, plan: &PatchPlan) -> io::Result<()> {
+    if !plan.appended.is_empty() {
+        writeln!(out, "$a")?;
+        for line in &plan.appended {
+            // A lone "." would end the input early, so it goes in doubled and
+            // is repaired below
+            if line == "." {
+                writeln!(out, "..")?;
+            } else {
+                writeln!(out, "{}", line)?;
+            }
+        }
+        writeln!(out, ".")?;
+        for (i, line) in plan.appended.iter().enumerate() {
+            if line == "." {
+                writeln!(out, "{}s/^\\.\\././", plan.original_len + i + 1)?;
+            }
+        }
+    }
+    for range in plan.removed_ranges().iter().rev() {
+        if range.len() == 1 {
+            writeln!(out, "{}d", range.start + 1)?;
+        } else {
+            writeln!(out, "{},{}d", range.start + 1, range.end)?;
+        }
+    }
+    Ok(())
+}
+
+// The sections of a three-way report: heading, class, and which file the
+// line numbers refer to
+fn three_way_sections<'a>(
+    base: &'a str,
+    first: &'a str,
+    second: &'a str,
+    report: &'a ThreeWayReport,
+) -> [(String, Class, &'a [ThreeWayLine]); 5] {
+    [
+        (
+            format!("LINES ADDED IN FIRST ({})", first),
+            Class::AddedInFirst,
+            &report.added_in_first,
+        ),
+        (
+            format!("LINES ADDED IN SECOND ({})", second),
+            Class::AddedInSecond,
+            &report.added_in_second,
+        ),
+        (
+            format!("LINES OF BASE ({}) REMOVED IN FIRST ({})", base, first),
+            Class::RemovedInFirst,
+            &report.removed_in_first,
+        ),
+        (
+            format!("LINES OF BASE ({}) REMOVED IN SECOND ({})", base, second),
+            Class::RemovedInSecond,
+            &report.removed_in_second,
+        ),
+        (
+            format!(
+                "CONFLICTING LINES OF BASE ({}): REMOVED ON ONE SIDE, CHANGED ON THE OTHER",
+                base
+            ),
+            Class::Conflicting,
+            &report.conflicting,
+        ),
+    ]
+}
+
+fn conflict_note(line: &ThreeWayLine) -> &'static str {
+    if line.removed_in_first() {
+        "removed in first"
+    } else {
+        "removed in second"
+    }
+}
+
+pub fn render_text_three_way(base: &str, first: &str, second: &str, report: &ThreeWayReport) {
+    print_separator();
+    for (heading, class, lines) in three_way_sections(base, first, second, report) {
+        println!("{}", heading);
+        print_separator();
+        for line in lines {
+            if class == Class::Conflicting {
+                println!(
+                    "line {}: {} ({})",
+                    line.line(class),
+                    line.text,
+                    conflict_note(line)
+                );
+            } else {
+                println!("line {}: {}", line.line(class), line.text);
+            }
+        }
+        print_separator();
+        println!("TOTAL: {}", lines.len());
+        print_separator();
+    }
+}
+
+pub fn render_html_three_way(base: &str, first: &str, second: &str, report: &ThreeWayReport) {
+    print_html_header();
+    println!("<table border=\"1\">");
+    for (heading, class, lines) in three_way_sections(base, first, second, report) {
+        println!(
+            "<tr class=table-section><td colspan=2>{}</td></tr>",
+            heading
+        );
+        println!("<tr class=table-header><th>Line Number</th><th>Text</th></tr>");
+        for line in lines {
+            if class == Class::Conflicting {
+                println!(
+                    "<tr class=table-body><td>{}</td><td>{} <i>({})</i></td></tr>",
+                    line.line(class),
+                    line.text,
+                    conflict_note(line)
+                );
+            } else {
+                println!(
+                    "<tr class=table-body><td>{}</td><td>{}</td></tr>",
+                    line.line(class),
+                    line.text
+                );
+            }
+        }
+        println!(
+            "<tr class=table-footer><td colspan=2>TOTAL: {}</td></tr>",
+            lines.len()
+        );
+    }
+    println!("</table>");
+    print_html_footer();
+}
+
+fn format_sources(files: &[String], sources: &[Source]) -> String {
+    sources
+        .iter()
+        .map(|source| format!("{}:{}", files[source.file], source.line))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+pub fn render_text_expr(files: &[String], lines: &[(Vec<Source>, String)]) {
+    print_separator();
+    println!("LINES IN RESULT OF EXPRESSION OVER ({})", files.join(", "));
+    print_separator();
+    for (sources, text) in lines {
+        println!("{}: {}", format_sources(files, sources), text);
+    }
+    print_separator();
+    println!("TOTAL: {}", lines.len());
+    print_separator();
+}
+
+pub fn render_html_expr(files: &[String], lines: &[(Vec<Source>, String)]) {
+    print_html_header();
+    println!("<table border=\"1\">");
+    println!(
+        "<tr class=table-section><td colspan=2>LINES IN RESULT OF EXPRESSION OVER (<b>{}</b>)</td></tr>",
+        files.join(", ")
+    );
+    println!("<tr class=table-header><th>Sources</th><th>Text</th></tr>");
+    for (sources, text) in lines {
+        println!(
+            "<tr class=table-body><td>{}</td><td>{}</td></tr>",
+            format_sources(files, sources),
+            text
+        );
+    }
+    println!(
+        "<tr class=table-footer><td colspan=2>TOTAL: {}</td></tr>",
+        lines.len()
+    );
+    println!("</table>");
+    print_html_footer();
+}
+
+pub fn render_text_coverage(first: &str, second: &str, coverage: &Coverage) {
+    print_separator();
+    println!(
+        "LINES IN FIRST ({}) FILE, NOT COVERED BY ANY PREFIX IN SECOND ({})",
+        first, second
+    );
+    print_separator();
+    for (num, text) in &coverage.uncovered {
+        println!("line {}: {}", num, text);
+    }
+    print_separator();
+    println!("TOTAL: {}", coverage.uncovered.len());
+    print_separator();
+    println!(
+        "LINES IN FIRST ({}) FILE, COVERED BY A PREFIX IN SECOND ({})",
+        first, second
+    );
+    print_separator();
+    for covered in &coverage.covered {
+        println!(
+            "line {}: {} (rule line {}: {})",
+            covered.line, covered.text, covered.rule_line, covered.rule
+        );
+    }
+    print_separator();
+    println!("TOTAL: {}", coverage.covered.len());
+    print_separator();
+    println!(
+        "PREFIXES IN SECOND ({}) FILE, COVERING NOTHING IN FIRST ({})",
+        second, first
+    );
+    print_separator();
+    for (num, text) in &coverage.unused_rules {
+        println!("line {}: {}", num, text);
+    }
+    print_separator();
+    println!("TOTAL: {}", coverage.unused_rules.len());
+    print_separator();
+}
+
+pub fn render_html_coverage(first: &str, second: &str, coverage: &Coverage) {
+    print_html_header();
+    println!("<table border=\"1\">");
+    println!(
+        "<tr class=table-section><td colspan=3>LINES IN FIRST (<b>{}</b>) FILE, NOT COVERED BY ANY PREFIX IN SECOND (<b>{}</b>)</td></tr>",
+        first, second
+    );
+    println!("<tr class=table-header><th>Line Number</th><th colspan=2>Text</th></tr>");
+    for (num, text) in &coverage.uncovered {
+        println!(
+            "<tr class=table-body><td>{}</td><td colspan=2>{}</td></tr>",
+            num, text
+        );
+    }
+    println!(
+        "<tr class=table-footer><td colspan=3>TOTAL: {}</td></tr>",
+        coverage.uncovered.len()
+    );
+    println!(
+        "<tr class=table-section><td colspan=3>LINES IN FIRST (<b>{}</b>) FILE, COVERED BY A PREFIX IN SECOND (<b>{}</b>)</td></tr>",
+        first, second
+    );
+    println!("<tr class=table-header><th>Line Number</th><th>Text</th><th>Rule</th></tr>");
+    for covered in &coverage.covered {
+        println!(
+            "<tr class=table-body><td>{}</td><td>{}</td><td>line {}: {}</td></tr>",
+            covered.line, covered.text, covered.rule_line, covered.rule
+        );
+    }
+    println!(
+        "<tr class=table-footer><td colspan=3>TOTAL: {}</td></tr>",
+        coverage.covered.len()
+    );
+    println!(
+        "<tr class=table-section><td colspan=3>PREFIXES IN SECOND (<b>{}</b>) FILE, COVERING NOTHING IN FIRST (<b>{}</b>)</td></tr>",
+        second, first
+    );
+    println!("<tr class=table-header><th>Line Number</th><th colspan=2>Text</th></tr>");
+    for (num, text) in &coverage.unused_rules {
+        println!(
+            "<tr class=table-body><td>{}</td><td colspan=2>{}</td></tr>",
+            num, text
+        );
+    }
+    println!(
+        "<tr class=table-footer><td colspan=3>TOTAL: {}</td></tr>",
+        coverage.unused_rules.len()
+    );
+    println!("</table>");
+    print_html_footer();
+}
+
+fn write_text_group(out: &mut dyn Write, group: &PrefixGroup, depth: usize) -> io::Result<()> {
+    let indent = "  ".repeat(depth);
+    for child in &group.groups {
+        writeln!(out, "{}{} ({})", indent, child.label, child.count)?;
+        write_text_group(out, child, depth + 1)?;
+    }
+    for (num, text) in &group.lines {
+        writeln!(out, "{}line {}: {}", indent, num, text)?;
+    }
+    Ok(())
+}
+
+/// `--group-by-prefix`: both lists of differences as trees of shared
+/// prefixes, headed by the summary of the two files.
+pub fn render_text_grouped_output(
+    out: &mut dyn Write,
+    first: &str,
+    second: &str,
+    summary: Summary,
+    groups_in_first_not_in_second: &PrefixGroup,
+    groups_in_second_not_in_first: &PrefixGroup,
+) -> io::Result<()> {
+    let mut stream = TextStream::new(&mut *out, first, second).with_summary(summary);
+    stream.start()?;
+    for (side, group) in [
+        (Side::FirstOnly, groups_in_first_not_in_second),
+        (Side::SecondOnly, groups_in_second_not_in_first),
+    ] {
+        stream.start_side(side)?;
+        write_text_group(stream.writer(), group, 0)?;
+        stream.end_side(group.count, false)?;
+    }
+    stream.finish()
+}
+
+fn write_html_group(out: &mut dyn Write, group: &PrefixGroup) -> io::Result<()> {
+    writeln!(out, "<ul>")?;
+    for child in &group.groups {
+        writeln!(
+            out,
+            "<li><details open><summary>{} ({})</summary>",
+            child.label, child.count
+        )?;
+        write_html_group(out, child)?;
+        writeln!(out, "</details></li>")?;
+    }
+    for (num, text) in &group.lines {
+        writeln!(out, "<li>line {}: {}</li>", num, text)?;
+    }
+    writeln!(out, "</ul>")
+}
+
+/// `--group-by-prefix` as an HTML table, headed by the summary of the two
+/// files.
+pub fn render_html_grouped_output(
+    out: &mut dyn Write,
+    first: &str,
+    second: &str,
+    summary: Summary,
+    groups_in_first_not_in_second: &PrefixGroup,
+    groups_in_second_not_in_first: &PrefixGroup,
+) -> io::Result<()> {
+    let mut stream = HtmlStream::new(&mut *out, first, second).with_summary(summary);
+    stream.start()?;
+    for (side, group) in [
+        (Side::FirstOnly, groups_in_first_not_in_second),
+        (Side::SecondOnly, groups_in_second_not_in_first),
+    ] {
+        let (this, other) = match side {
+            Side::FirstOnly => (("FIRST", first), ("SECOND", second)),
+            Side::SecondOnly => (("SECOND", second), ("FIRST", first)),
+        };
+        let out = stream.writer();
+        writeln!(
+            out,
+            "<tr class=table-section><td colspan=2>LINES IN {} (<b>{}</b>) FILE, BUT NOT IN {} (<b>{}</b>)</td></tr>",
+            this.0, this.1, other.0, other.1
+        )?;
+        writeln!(out, "<tr class=table-body><td colspan=2>")?;
+        write_html_group(&mut *out, group)?;
+        writeln!(out, "</td></tr>")?;
+        stream.end_side(group.count, false)?;
+    }
+    stream.finish()
+}
+
+pub fn render_text_patterns(first: &str, second: &str, report: &PatternReport) {
+    print_separator();
+    println!(
+        "LINES IN FIRST ({}) FILE, MATCHING NO LINE OR PATTERN IN SECOND ({})",
+        first, second
+    );
+    print_separator();
+    for (num, text) in &report.unmatched {
+        println!("line {}: {}", num, text);
+    }
+    print_separator();
+    println!("TOTAL: {}", report.unmatched.len());
+    print_separator();
+    println!(
+        "LINES AND PATTERNS IN SECOND ({}) FILE, MATCHING NOTHING IN FIRST ({})",
+        second, first
+    );
+    print_separator();
+    for (num, text) in &report.unused {
+        println!("line {}: {}", num, text);
+    }
+    print_separator();
+    println!("TOTAL: {}", report.unused.len());
+    print_separator();
+}
+
+pub fn render_html_patterns(first: &str, second: &str, report: &PatternReport) {
+    print_html_header();
+    println!("<table border=\"1\">");
+    println!(
+        "<tr class=table-section><td colspan=2>LINES IN FIRST (<b>{}</b>) FILE, MATCHING NO LINE OR PATTERN IN SECOND (<b>{}</b>)</td></tr>",
+        first, second
+    );
+    println!("<tr class=table-header><th>Line Number</th><th>Text</th></tr>");
+    for (num, text) in &report.unmatched {
+        println!(
+            "<tr class=table-body><td>{}</td><td>{}</td></tr>",
+            num, text
+        );
+    }
+    println!(
+        "<tr class=table-footer><td colspan=2>TOTAL: {}</td></tr>",
+        report.unmatched.len()
+    );
+    println!(
+        "<tr class=table-section><td colspan=2>LINES AND PATTERNS IN SECOND (<b>{}</b>) FILE, MATCHING NOTHING IN FIRST (<b>{}</b>)</td></tr>",
+        second, first
+    );
+    println!("<tr class=table-header><th>Line Number</th><th>Pattern</th></tr>");
+    for (num, text) in &report.unused {
+        println!(
+            "<tr class=table-body><td>{}</td><td>{}</td></tr>",
+            num, text
+        );
+    }
+    println!(
+        "<tr class=table-footer><td colspan=2>TOTAL: {}</td></tr>",
+        report.unused.len()
+    );
+    println!("</table>");
+    print_html_footer();
+}
+
+pub fn render_text_triage(first: &str, second: &str, triage: &Triage) {
+    print_separator();
+    println!(
+        "SAMPLE OF LINES IN FIRST ({}) FILE, MISSING FROM SECOND ({})",
+        first, second
+    );
+    print_separator();
+    for (num, text) in &triage.sample {
+        println!("line {}: {}", num, text);
+    }
+    print_separator();
+    println!("LINES CHECKED: {}", triage.checked);
+    println!("MISSING: {}", triage.missing);
+    println!(
+        "ESTIMATED MISSING: {} (false-positive rate {:.6})",
+        triage.estimated_missing, triage.false_positive_rate
+    );
+    println!(
+        "FILTER: {} bytes, {} hashes",
+        triage.filter_bytes, triage.filter_hashes
+    );
+    print_separator();
+}
+
+pub fn render_html_triage(first: &str, second: &str, triage: &Triage) {
+    print_html_header();
+    println!("<table border=\"1\">");
+    println!(
+        "<tr class=table-section><td colspan=2>SAMPLE OF LINES IN FIRST (<b>{}</b>) FILE, MISSING FROM SECOND (<b>{}</b>)</td></tr>",
+        first, second
+    );
+    println!("<tr class=table-header><th>Line Number</th><th>Text</th></tr>");
+    for (num, text) in &triage.sample {
+        println!(
+            "<tr class=table-body><td>{}</td><td>{}</td></tr>",
+            num, text
+        );
+    }
+    println!(
+        "<tr class=table-footer><td colspan=2>LINES CHECKED: {}</td></tr>",
+        triage.checked
+    );
+    println!(
+        "<tr class=table-footer><td colspan=2>MISSING: {}</td></tr>",
+        triage.missing
+    );
+    println!(
+        "<tr class=table-footer><td colspan=2>ESTIMATED MISSING: {} (false-positive rate {:.6})</td></tr>",
+        triage.estimated_missing, triage.false_positive_rate
+    );
+    println!(
+        "<tr class=table-footer><td colspan=2>FILTER: {} bytes, {} hashes</td></tr>",
+        triage.filter_bytes, triage.filter_hashes
+    );
+    println!("</table>");
+    print_html_footer();
+}
+
+fn snapshot_rows(header: &SnapshotHeader) -> [(&'static str, String); 4] {
+    [
+        (
+            "NORMALIZATION",
+            if header.ignore_case {
+                "ignore case".to_string()
+            } else {
+                "none".to_string()
+            },
+        ),
+        ("SOURCE CHECKSUM", format!("{:032x}", header.source_checksum)),
+        ("LINES", header.total_lines.to_string()),
+        ("DISTINCT LINES", header.keys.to_string()),
+    ]
+}
+
+pub fn render_text_snapshot_info(snapshot: &str, header: &SnapshotHeader) {
+    print_separator();
+    println!("SNAPSHOT ({})", snapshot);
+    print_separator();
+    for (label, value) in snapshot_rows(header) {
+        println!("{}: {}", label, value);
+    }
+    print_separator();
+}
+
+pub fn render_html_snapshot_info(snapshot: &str, header: &SnapshotHeader) {
+    print_html_header();
+    println!("<table border=\"1\">");
+    println!(
+        "<tr class=table-section><td colspan=2>SNAPSHOT (<b>{}</b>)</td></tr>",
+        snapshot
+    );
+    for (label, value) in snapshot_rows(header) {
+        println!(
+            "<tr class=table-body><td>{}</td><td>{}</td></tr>",
+            label, value
+        );
+    }
+    println!("</table>");
+    print_html_footer();
+}
+
+/// `text` as a quoted JSON string.
+pub(crate) fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Versions a flapping line came back in
+fn returns(timeline: &Timeline, line: &LineHistory) -> Vec<String> {
+    line.runs[1..]
+        .iter()
+        .map(|(start, _)| version_label(timeline, *start))
+        .collect()
+}
+
+// Version `index` by its file and its place in the series, as the same file
+// may be given more than once
+fn version_label(timeline: &Timeline, index: usize) -> String {
+    format!("{} (file {})", timeline.files[index], index)
+}
+
+pub fn render_text_history(timeline: &Timeline) {
+    let versions = timeline.files.len();
+    print_separator();
+    println!("HISTORY OF {} FILES", versions);
+    print_separator();
+    for (index, file) in timeline.files.iter().enumerate() {
+        println!("file {}: {}", index, file);
+    }
+    print_separator();
+    for line in &timeline.lines {
+        let mut events = vec![format!(
+            "appeared {}",
+            version_label(timeline, line.appeared())
+        )];
+        if let Some(version) = line.disappeared(versions) {
+            events.push(format!("disappeared {}", version_label(timeline, version)));
+        }
+        if line.flapped() {
+            events.push(format!(
+                "flapped (back in {})",
+                returns(timeline, line).join(", ")
+            ));
+        }
+        println!("{}: {}", events.join(", "), line.text);
+    }
+    print_separator();
+    println!("CHANGED: {}", timeline.lines.len());
+    println!("FLAPPED: {}", timeline.flapped());
+    println!("UNCHANGED: {}", timeline.unchanged);
+    print_separator();
+}
+
+pub fn render_html_history(timeline: &Timeline) {
+    let versions = timeline.files.len();
+    print_html_header();
+    println!("<table border=\"1\">");
+    println!(
+        "<tr class=table-section><td colspan=4>HISTORY OF {} FILES ({})</td></tr>",
+        versions,
+        timeline
+            .files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| format!("{}: <b>{}</b>", index, file))
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+    println!(
+        "<tr class=table-header><th>Appeared</th><th>Disappeared</th><th>Flapped</th><th>Text</th></tr>"
+    );
+    for line in &timeline.lines {
+        let disappeared = line
+            .disappeared(versions)
+            .map_or(String::new(), |version| version_label(timeline, version));
+        let flapped = if line.flapped() {
+            format!("back in {}", returns(timeline, line).join(", "))
+        } else {
+            String::new()
+        };
+        println!(
+            "<tr class=table-body><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            version_label(timeline, line.appeared()),
+            disappeared,
+            flapped,
+            line.text
+        );
+    }
+    println!(
+        "<tr class=table-footer><td colspan=4>CHANGED: {}</td></tr>",
+        timeline.lines.len()
+    );
+    println!(
+        "<tr class=table-footer><td colspan=4>FLAPPED: {}</td></tr>",
+        timeline.flapped()
+    );
+    println!(
+        "<tr class=table-footer><td colspan=4>UNCHANGED: {}</td></tr>",
+        timeline.unchanged
+    );
+    println!("</table>");
+    print_html_footer();
+}
+
+/// The timeline as one JSON object; `runs` give the first and last file of
+/// each stretch a line was present. Each file is given as its index in
+/// `files` together with its name.
+pub fn render_json_history(timeline: &Timeline) {
+    let versions = timeline.files.len();
+    let files: Vec<String> = timeline.files.iter().map(|f| json_string(f)).collect();
+    let version = |index: usize| format!("{{\"version\": {}, \"file\": {}}}", index, files[index]);
+    println!("{{");
+    println!("  \"files\": [{}],", files.join(", "));
+    println!("  \"unchanged\": {},", timeline.unchanged);
+    println!("  \"lines\": [");
+    for (index, line) in timeline.lines.iter().enumerate() {
+        let disappeared = line
+            .disappeared(versions)
+            .map_or("null".to_string(), version);
+        let runs: Vec<String> = line
+            .runs
+            .iter()
+            .map(|(start, end)| {
+                format!(
+                    "{{\"from\": {}, \"to\": {}}}",
+                    version(*start),
+                    version(end - 1)
+                )
+            })
+            .collect();
+        let separator = if index + 1 < timeline.lines.len() {
+            ","
+        } else {
+            ""
+        };
+        println!(
+            "    {{\"text\": {}, \"appeared\": {}, \"disappeared\": {}, \"flapped\": {}, \"runs\": [{}]}}{}",
+            json_string(&line.text),
+            version(line.appeared()),
+            disappeared,
+            line.flapped(),
+            runs.join(", "),
+            separator
+        );
+    }
+    println!("  ]");
+    println!("}}");
+}
+
+// `items` gathered by the folder of their relative path, the top-level
+// folder `""` first, keeping their order within each folder
+fn by_folder<'a, T>(
+    items: &'a [T],
+    path: impl Fn(&'a T) -> &'a str,
+) -> BTreeMap<&'a str, Vec<&'a T>> {
+    let mut folders: BTreeMap<&str, Vec<&T>> = BTreeMap::new();
+    for item in items {
+        let folder = path(item).rsplit_once('/').map_or("", |(folder, _)| folder);
+        folders.entry(folder).or_default().push(item);
+    }
+    folders
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit_once('/').map_or(path, |(_, name)| name)
+}
+
+fn print_text_paths(paths: &[String]) {
+    for (folder, paths) in by_folder(paths, String::as_str) {
+        let indent = if folder.is_empty() {
+            ""
+        } else {
+            println!("{}/", folder);
+            "  "
+        };
+        for path in paths {
+            println!("{}{}", indent, file_name(path));
+        }
+    }
+}
+
+fn file_comparison_text(file: &FileComparison) -> String {
+    if file.same_lines() {
+        format!("same lines ({} distinct)", file.summary.common)
+    } else {
+        format!(
+            "{} only in first, {} only in second, {} common, jaccard {:.4}",
+            file.only_in_first(),
+            file.only_in_second(),
+            file.summary.common,
+            file.summary.jaccard()
+        )
+    }
+}
+
+pub fn render_text_directories(comparison: &DirectoryComparison) {
+    let (first, second) = (&comparison.first, &comparison.second);
+    print_separator();
+    println!("FILES ONLY IN FIRST ({}) DIRECTORY", first);
+    print_separator();
+    print_text_paths(&comparison.only_in_first);
+    print_separator();
+    println!("TOTAL: {}", comparison.only_in_first.len());
+    print_separator();
+    println!("FILES ONLY IN SECOND ({}) DIRECTORY", second);
+    print_separator();
+    print_text_paths(&comparison.only_in_second);
+    print_separator();
+    println!("TOTAL: {}", comparison.only_in_second.len());
+    print_separator();
+    println!("FILES IN BOTH FIRST ({}) AND SECOND ({})", first, second);
+    print_separator();
+    for (folder, files) in by_folder(&comparison.in_both, |file| file.path.as_str()) {
+        let indent = if folder.is_empty() {
+            ""
+        } else {
+            println!("{}/", folder);
+            "  "
+        };
+        for file in files {
+            println!(
+                "{}{}: {}",
+                indent,
+                file_name(&file.path),
+                file_comparison_text(file)
+            );
+        }
+    }
+    print_separator();
+    println!(
+        "TOTAL: {} ({} with different lines)",
+        comparison.in_both.len(),
+        comparison.differing()
+    );
+    print_separator();
+}
+
+fn print_html_paths(paths: &[String]) {
+    for (folder, paths) in by_folder(paths, String::as_str) {
+        if !folder.is_empty() {
+            println!("<tr class=table-header><th colspan=5>{}/</th></tr>", folder);
+        }
+        for path in paths {
+            println!(
+                "<tr class=table-body><td colspan=5>{}</td></tr>",
+                file_name(path)
+            );
+        }
+    }
+}
+
+pub fn render_html_directories(comparison: &DirectoryComparison) {
+    let (first, second) = (&comparison.first, &comparison.second);
+    print_html_header();
+    println!("<table border=\"1\">");
+    for (heading, paths) in [
+        (
+            format!("FILES ONLY IN FIRST (<b>{}</b>) DIRECTORY", first),
+            &comparison.only_in_first,
+        ),
+        (
+            format!("FILES ONLY IN SECOND (<b>{}</b>) DIRECTORY", second),
+            &comparison.only_in_second,
+        ),
+    ] {
+        println!(
+            "<tr class=table-section><td colspan=5>{}</td></tr>",
+            heading
+        );
+        print_html_paths(paths);
+        println!(
+            "<tr class=table-footer><td colspan=5>TOTAL: {}</td></tr>",
+            paths.len()
+        );
+    }
+    println!(
+        "<tr class=table-section><td colspan=5>FILES IN BOTH FIRST (<b>{}</b>) AND SECOND (<b>{}</b>)</td></tr>",
+        first, second
+    );
+    println!(
+        "<tr class=table-header><th>File</th><th>Only In First</th><th>Only In Second</th><th>Common</th><th>Jaccard</th></tr>"
+    );
+    for (folder, files) in by_folder(&comparison.in_both, |file| file.path.as_str()) {
+        if !folder.is_empty() {
+            println!("<tr class=table-header><th colspan=5>{}/</th></tr>", folder);
+        }
+        for file in files {
+            println!(
+                "<tr class=table-body><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.4}</td></tr>",
+                file_name(&file.path),
+                file.only_in_first(),
+                file.only_in_second(),
+                file.summary.common,
+                file.summary.jaccard()
+            );
+        }
+    }
+    println!(
+        "<tr class=table-footer><td colspan=5>TOTAL: {} ({} with different lines)</td></tr>",
+        comparison.in_both.len(),
+        comparison.differing()
+    );
+    println!("</table>");
+    print_html_footer();
+}
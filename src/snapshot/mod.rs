@@ -0,0 +1,264 @@
+//! `snapshot create`: a symbol table saved to disk, to compare against
+//! without rebuilding it.
+//!
+//! A snapshot holds every key of a file's table with its line number, the
+//! normalization the keys were made with, the file's count of non-empty
+//! lines and a checksum of its bytes. `--second` accepts a snapshot in place
+//! of a file; it is recognised by its magic bytes, not its name, and refused
+//! if it was made with different normalization options.
+//!
+//! The layout, with integers little-endian:
+//!
+//! ```text
+//! magic       8 bytes   "FCSNAP" and a u16 format version
+//! flags       u8        bit 0: keys are upper-cased (`--ignore-case`)
+//! checksum    u128      128-bit FNV-1a of the source file's bytes
+//! total lines u64       non-empty lines of the source, counting repeats
+//! keys        u64       number of entries that follow
+//! entries               in key order, each: varint bytes shared with the
+//!                       previous key, varint length of the rest, the rest,
+//!                       varint line number
+//! ```
+
+use crate::archive::open_member;
+use crate::build_symbol_table;
+use crate::fingerprint::{extend_fingerprint, fingerprint};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use tries::{SymbolTable, TernarySearchTrie};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+const MAGIC: &[u8; 6] = b"FCSNAP";
+const VERSION: u16 = 1;
+const IGNORE_CASE: u8 = 1;
+
+/// What a snapshot says about itself, ahead of its entries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapshotHeader {
+    pub ignore_case: bool,
+    pub source_checksum: u128,
+    pub total_lines: u64,
+    pub keys: u64,
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Whether `filename` starts with the snapshot magic bytes. Only regular
+/// files are looked at, so a pipe never loses bytes to the check.
+pub fn is_snapshot(filename: &str) -> bool {
+    let mut magic = [0; MAGIC.len()];
+    std::fs::metadata(filename).is_ok_and(|metadata| metadata.is_file())
+        && File::open(filename)
+            .and_then(|mut file| file.read_exact(&mut magic))
+            .is_ok_and(|_| magic == *MAGIC)
+}
+
+/// 128-bit FNV-1a of the bytes of `filename`, which may be an archive
+/// member.
+pub fn file_checksum(filename: &str) -> io::Result<u128> {
+    let mut file: Box<dyn Read> = match open_member(filename) {
+        Some(member) => Box::new(member?),
+        None => Box::new(File::open(filename)?),
+    };
+    let mut buffer = vec![0; 64 * 1024];
+    let mut hash = fingerprint("");
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(hash);
+        }
+        hash = extend_fingerprint(hash, &buffer[..read]);
+    }
+}
+
+fn write_varint(out: &mut impl Write, mut value: u64) -> io::Result<()> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return out.write_all(&[byte]);
+        }
+        out.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_varint(input: &mut impl Read) -> io::Result<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0];
+        input.read_exact(&mut byte)?;
+        value |= ((byte[0] & 0x7f) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("malformed number in snapshot"))
+}
+
+// Bytes `key` shares with `previous`, backed off to a character boundary
+fn shared_prefix(previous: &str, key: &str) -> usize {
+    let mut shared = previous
+        .bytes()
+        .zip(key.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !key.is_char_boundary(shared) {
+        shared -= 1;
+    }
+    shared
+}
+
+/// Writes `symbol_table` as a snapshot.
+pub fn write_snapshot<T: SymbolTable<u32>>(
+    out: &mut impl Write,
+    symbol_table: &T,
+    ignore_case: bool,
+    source_checksum: u128,
+    total_lines: u64,
+) -> io::Result<()> {
+    out.write_all(MAGIC)?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    out.write_all(&[if ignore_case { IGNORE_CASE } else { 0 }])?;
+    out.write_all(&source_checksum.to_le_bytes())?;
+    out.write_all(&total_lines.to_le_bytes())?;
+    out.write_all(&(symbol_table.get_size() as u64).to_le_bytes())?;
+
+    let mut previous = String::new();
+    let mut result = Ok(());
+    symbol_table.for_each_entry(&mut |key, line| {
+        let shared = shared_prefix(&previous, key);
+        let rest = &key.as_bytes()[shared..];
+        result = write_varint(out, shared as u64)
+            .and_then(|_| write_varint(out, rest.len() as u64))
+            .and_then(|_| out.write_all(rest))
+            .and_then(|_| write_varint(out, *line as u64));
+        previous.clear();
+        previous.push_str(key);
+        result.is_ok()
+    });
+    result
+}
+
+pub fn read_header(input: &mut impl Read) -> io::Result<SnapshotHeader> {
+    let mut magic = [0; MAGIC.len()];
+    input.read_exact(&mut magic)?;
+    if magic != *MAGIC {
+        return Err(invalid_data("not a snapshot"));
+    }
+    let mut version = [0; 2];
+    input.read_exact(&mut version)?;
+    if u16::from_le_bytes(version) != VERSION {
+        return Err(invalid_data(format!(
+            "unsupported snapshot version {}",
+            u16::from_le_bytes(version)
+        )));
+    }
+    let mut flags = [0; 1];
+    let mut checksum = [0; 16];
+    let mut total_lines = [0; 8];
+    let mut keys = [0; 8];
+    input.read_exact(&mut flags)?;
+    input.read_exact(&mut checksum)?;
+    input.read_exact(&mut total_lines)?;
+    input.read_exact(&mut keys)?;
+    Ok(SnapshotHeader {
+        ignore_case: flags[0] & IGNORE_CASE != 0,
+        source_checksum: u128::from_le_bytes(checksum),
+        total_lines: u64::from_le_bytes(total_lines),
+        keys: u64::from_le_bytes(keys),
+    })
+}
+
+/// Reads the entries following a header into `symbol_table`.
+pub fn read_entries<T: SymbolTable<u32>>(
+    input: &mut impl Read,
+    header: &SnapshotHeader,
+    symbol_table: &mut T,
+) -> io::Result<()> {
+    let mut entries = Vec::new();
+    let mut key: Vec<u8> = Vec::new();
+    for _ in 0..header.keys {
+        let shared = read_varint(input)? as usize;
+        let rest = read_varint(input)? as usize;
+        if shared > key.len() {
+            return Err(invalid_data("malformed key in snapshot"));
+        }
+        key.truncate(shared);
+        // Grown as bytes arrive, so a corrupt length cannot allocate wildly
+        input.take(rest as u64).read_to_end(&mut key)?;
+        if key.len() != shared + rest {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let line = u32::try_from(read_varint(input)?)
+            .map_err(|_| invalid_data("line number out of range in snapshot"))?;
+        let text =
+            String::from_utf8(key.clone()).map_err(|_| invalid_data("key is not UTF-8"))?;
+        entries.push((text, line));
+    }
+
+    // Keys come sorted; putting them in that order would make every level
+    // of a ternary trie a linked list, so put medians first
+    let mut ranges = vec![(0, entries.len())];
+    while let Some((start, end)) = ranges.pop() {
+        if start == end {
+            continue;
+        }
+        let middle = start + (end - start) / 2;
+        let (text, line) = std::mem::take(&mut entries[middle]);
+        symbol_table.put(text, line);
+        ranges.push((middle + 1, end));
+        ranges.push((start, middle));
+    }
+    Ok(())
+}
+
+/// Loads the snapshot `filename` into a table, returning it with the
+/// source's count of non-empty lines. Refuses a snapshot made with other
+/// normalization options than `ignore_case`.
+pub fn load_snapshot<T: SymbolTable<u32> + Default>(
+    filename: &str,
+    ignore_case: bool,
+) -> io::Result<(T, usize)> {
+    let mut input = BufReader::new(File::open(filename)?);
+    let header = read_header(&mut input)?;
+    if header.ignore_case != ignore_case {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "snapshot {} was created {} --ignore-case",
+                filename,
+                if header.ignore_case { "with" } else { "without" }
+            ),
+        ));
+    }
+    let mut symbol_table = T::default();
+    read_entries(&mut input, &header, &mut symbol_table)?;
+    Ok((symbol_table, header.total_lines as usize))
+}
+
+/// Builds the table of `source` and saves it as the snapshot `output`.
+pub fn create_snapshot(source: &str, output: &str, ignore_case: bool) -> io::Result<()> {
+    if source == "-" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the source is read twice for its checksum and cannot be standard input",
+        ));
+    }
+    let source_checksum = file_checksum(source)?;
+    let mut symbol_table = TernarySearchTrie::new();
+    let total_lines = build_symbol_table(source, &mut symbol_table, ignore_case)?;
+
+    let mut out = BufWriter::new(File::create(output)?);
+    write_snapshot(
+        &mut out,
+        &symbol_table,
+        ignore_case,
+        source_checksum,
+        total_lines as u64,
+    )?;
+    out.flush()
+}
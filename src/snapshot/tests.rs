@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    use crate::backend::BTreeMapTable;
+    use crate::snapshot::{
+        create_snapshot, file_checksum, is_snapshot, load_snapshot, read_entries, read_header,
+        write_snapshot,
+    };
+    use crate::test_util::temp_path;
+    use std::io::{self, Cursor};
+    use tries::{PatriciaTrie, SymbolTable, TernarySearchTrie};
+
+    fn entries<T: SymbolTable<u32>>(table: &T) -> Vec<(String, u32)> {
+        let mut entries = Vec::new();
+        table.for_each_entry(&mut |key, line| {
+            entries.push((key.to_string(), *line));
+            true
+        });
+        entries
+    }
+
+    fn sample_table() -> TernarySearchTrie<u32> {
+        let mut table = TernarySearchTrie::new();
+        for (line, key) in ["apply", "application", "app", "zebra", "äpfel", "äpfeln", "b"]
+            .iter()
+            .enumerate()
+        {
+            table.put(key.to_string(), line as u32 * 1000);
+        }
+        table
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let table = sample_table();
+        let mut bytes = Vec::new();
+        write_snapshot(&mut bytes, &table, true, 0xfeed, 9).unwrap();
+
+        let mut input = Cursor::new(bytes);
+        let header = read_header(&mut input).unwrap();
+        assert!(header.ignore_case);
+        assert_eq!(
+            (header.source_checksum, header.total_lines, header.keys),
+            (0xfeed, 9, 7)
+        );
+        let mut loaded = PatriciaTrie::new();
+        read_entries(&mut input, &header, &mut loaded).unwrap();
+        assert_eq!(entries(&loaded), entries(&table));
+    }
+
+    #[test]
+    fn test_truncated_snapshot() {
+        let mut bytes = Vec::new();
+        write_snapshot(&mut bytes, &sample_table(), false, 0, 7).unwrap();
+        for len in [3, 20, bytes.len() - 1] {
+            let mut input = Cursor::new(&bytes[..len]);
+            let result = read_header(&mut input).and_then(|header| {
+                read_entries(&mut input, &header, &mut TernarySearchTrie::new())
+            });
+            assert_eq!(
+                result.unwrap_err().kind(),
+                io::ErrorKind::UnexpectedEof,
+                "{} bytes",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_create_and_load() {
+        let source = temp_path("source.txt");
+        let snapshot = temp_path("source.snap");
+        std::fs::write(&source, "beta\nalpha\n\nBeta\ngamma\nalpha\n").unwrap();
+        let (source_name, snapshot_name) = (source.as_str(), snapshot.as_str());
+
+        create_snapshot(source_name, snapshot_name, true).unwrap();
+        assert!(is_snapshot(snapshot_name));
+        assert!(!is_snapshot(source_name));
+
+        let header = read_header(&mut std::fs::File::open(&snapshot).unwrap()).unwrap();
+        assert_eq!(header.source_checksum, file_checksum(source_name).unwrap());
+
+        let (table, total_lines): (BTreeMapTable<u32>, usize) =
+            load_snapshot(snapshot_name, true).unwrap();
+        assert_eq!(total_lines, 5);
+        assert_eq!(
+            entries(&table),
+            vec![
+                ("ALPHA".to_string(), 5),
+                ("BETA".to_string(), 3),
+                ("GAMMA".to_string(), 4),
+            ]
+        );
+
+        let Err(error) = load_snapshot::<TernarySearchTrie<u32>>(snapshot_name, false) else {
+            panic!("loaded a snapshot made with other normalization");
+        };
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(error.to_string().contains("with --ignore-case"));
+    }
+}
@@ -0,0 +1,184 @@
+//! `--stream`: pushing differences to the report as they are found.
+//!
+//! The default report collects and sorts both difference lists before
+//! printing anything. Streaming instead walks the first table in key order
+//! and hands each line missing from the second straight to a
+//! [`StreamRenderer`], then does the same the other way round, so output
+//! starts at once and nothing is collected.
+//!
+//! Sorting by line number is still available within bounded memory: with
+//! `--limit N` only the N lowest line numbers are kept, and otherwise the
+//! lines go through the on-disk sort of `--memory-limit`.
+
+use crate::external::{DiskComparison, ExternalSorter, Record, SortOrder, SpillDir};
+use std::collections::BinaryHeap;
+use std::io;
+use tries::SymbolTable;
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+/// Which list of differences is being rendered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    /// Lines in the first file but not in the second
+    FirstOnly,
+    /// Lines in the second file but not in the first
+    SecondOnly,
+}
+
+/// A report written a line at a time: `start`, then for each side
+/// `start_side`, its lines and `end_side`, then `finish`.
+pub trait StreamRenderer {
+    fn start(&mut self) -> io::Result<()>;
+    fn start_side(&mut self, side: Side) -> io::Result<()>;
+    fn line(&mut self, num: u32, text: &str) -> io::Result<()>;
+    /// `truncated` is set when `--limit` stopped the side before its end.
+    fn end_side(&mut self, shown: usize, truncated: bool) -> io::Result<()>;
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+/// How the lines of each side are ordered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamOrder {
+    /// As found, in key order; nothing is held back
+    Unsorted,
+    /// By line number, sorting within about this many bytes
+    ByLine(usize),
+}
+
+// Passes lines on to the renderer until the limit is reached
+struct Emitter<'a> {
+    renderer: &'a mut dyn StreamRenderer,
+    limit: Option<usize>,
+    shown: usize,
+    truncated: bool,
+}
+
+impl<'a> Emitter<'a> {
+    fn new(renderer: &'a mut dyn StreamRenderer, limit: Option<usize>) -> Self {
+        Emitter {
+            renderer,
+            limit,
+            shown: 0,
+            truncated: false,
+        }
+    }
+
+    // Renders one line, or notes that the limit cut the side short. Returns
+    // whether more lines are wanted.
+    fn emit(&mut self, num: u32, text: &str) -> io::Result<bool> {
+        if self.limit.is_some_and(|limit| self.shown >= limit) {
+            self.truncated = true;
+            return Ok(false);
+        }
+        self.renderer.line(num, text)?;
+        self.shown += 1;
+        Ok(true)
+    }
+
+    fn end(self) -> io::Result<()> {
+        self.renderer.end_side(self.shown, self.truncated)
+    }
+}
+
+/// Streams the keys of each table missing from the other.
+pub fn stream_differences<T: SymbolTable<u32>>(
+    symbol_table_1: &T,
+    symbol_table_2: &T,
+    order: StreamOrder,
+    limit: Option<usize>,
+    renderer: &mut dyn StreamRenderer,
+) -> io::Result<()> {
+    renderer.start()?;
+    for (side, table, other) in [
+        (Side::FirstOnly, symbol_table_1, symbol_table_2),
+        (Side::SecondOnly, symbol_table_2, symbol_table_1),
+    ] {
+        renderer.start_side(side)?;
+        let mut emitter = Emitter::new(renderer, limit);
+        match (order, limit) {
+            (StreamOrder::Unsorted, _) => {
+                let mut result = Ok(());
+                table.for_each_entry(&mut |key, line| {
+                    if other.contains(key) {
+                        return true;
+                    }
+                    match emitter.emit(*line, key) {
+                        Ok(more) => more,
+                        Err(error) => {
+                            result = Err(error);
+                            false
+                        }
+                    }
+                });
+                result?;
+            }
+            (StreamOrder::ByLine(_), Some(limit)) => {
+                // A max-heap of the lowest line numbers seen; one beyond the
+                // limit tells whether the side was cut short
+                let mut lowest: BinaryHeap<Record> = BinaryHeap::new();
+                table.for_each_entry(&mut |key, line| {
+                    if !other.contains(key)
+                        && (lowest.len() <= limit || *line < lowest.peek().unwrap().0)
+                    {
+                        lowest.push((*line, key.to_string()));
+                        if lowest.len() > limit + 1 {
+                            lowest.pop();
+                        }
+                    }
+                    true
+                });
+                emit_all(&mut emitter, lowest.into_sorted_vec().into_iter().map(Ok))?;
+            }
+            (StreamOrder::ByLine(budget), None) => {
+                let dir = SpillDir::new()?;
+                let mut sorter = ExternalSorter::new(&dir, SortOrder::ByLine, budget);
+                let mut result = Ok(());
+                table.for_each_entry(&mut |key, line| {
+                    if !other.contains(key) {
+                        result = sorter.push((*line, key.to_string()));
+                    }
+                    result.is_ok()
+                });
+                result?;
+                emit_all(&mut emitter, sorter.finish()?)?;
+            }
+        }
+        emitter.end()?;
+    }
+    renderer.finish()
+}
+
+/// Streams the comparison of `--memory-limit`: each side's lines are rendered
+/// as they are read back from disk.
+pub fn stream_external(
+    mut comparison: DiskComparison,
+    limit: Option<usize>,
+    renderer: &mut dyn StreamRenderer,
+) -> io::Result<()> {
+    renderer.start()?;
+    for (side, records) in [
+        (Side::FirstOnly, &mut comparison.first_only),
+        (Side::SecondOnly, &mut comparison.second_only),
+    ] {
+        renderer.start_side(side)?;
+        let mut emitter = Emitter::new(renderer, limit);
+        emit_all(&mut emitter, records)?;
+        emitter.end()?;
+    }
+    renderer.finish()
+}
+
+fn emit_all<I>(emitter: &mut Emitter, records: I) -> io::Result<()>
+where
+    I: IntoIterator<Item = io::Result<Record>>,
+{
+    for record in records {
+        let (num, text) = record?;
+        if !emitter.emit(num, &text)? {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod tests {
+    use crate::comparer::ComparisonResult;
+    use crate::keys_missing_from;
+    use crate::render::{HtmlRenderer, HtmlStream, Renderer, TextRenderer, TextStream};
+    use crate::stream::{Side, StreamOrder, StreamRenderer, stream_differences};
+    use crate::summary::summarize;
+    use std::io;
+    use tries::{SymbolTable, TernarySearchTrie};
+
+    // Small enough that the sorted path spills several runs
+    const TINY_BUDGET: usize = 128;
+
+    // A side as recorded: which one, its lines, the count shown and whether
+    // it was truncated
+    type RecordedSide = (Side, Vec<(u32, String)>, usize, bool);
+
+    #[derive(Default)]
+    struct Recorder {
+        sides: Vec<RecordedSide>,
+        finished: bool,
+    }
+
+    impl StreamRenderer for Recorder {
+        fn start(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn start_side(&mut self, side: Side) -> io::Result<()> {
+            self.sides.push((side, Vec::new(), 0, false));
+            Ok(())
+        }
+
+        fn line(&mut self, num: u32, text: &str) -> io::Result<()> {
+            self.sides.last_mut().unwrap().1.push((num, text.to_string()));
+            Ok(())
+        }
+
+        fn end_side(&mut self, shown: usize, truncated: bool) -> io::Result<()> {
+            let side = self.sides.last_mut().unwrap();
+            side.2 = shown;
+            side.3 = truncated;
+            Ok(())
+        }
+
+        fn finish(&mut self) -> io::Result<()> {
+            self.finished = true;
+            Ok(())
+        }
+    }
+
+    fn table(lines: &[&str]) -> TernarySearchTrie<u32> {
+        let mut table = TernarySearchTrie::new();
+        for (index, line) in lines.iter().enumerate() {
+            table.put(line.to_string(), index as u32);
+        }
+        table
+    }
+
+    fn tables() -> (TernarySearchTrie<u32>, TernarySearchTrie<u32>) {
+        (
+            table(&["pear", "apple", "fig", "kiwi", "date", "plum"]),
+            table(&["fig", "lime", "apple", "cherry"]),
+        )
+    }
+
+    fn record(order: StreamOrder, limit: Option<usize>) -> Recorder {
+        let (first, second) = tables();
+        let mut recorder = Recorder::default();
+        stream_differences(&first, &second, order, limit, &mut recorder).unwrap();
+        recorder
+    }
+
+    fn lines(records: &[(u32, &str)]) -> Vec<(u32, String)> {
+        records
+            .iter()
+            .map(|(num, text)| (*num, text.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_unsorted_in_key_order() {
+        let recorder = record(StreamOrder::Unsorted, None);
+        assert!(recorder.finished);
+        assert_eq!(recorder.sides.len(), 2);
+        assert_eq!(recorder.sides[0].0, Side::FirstOnly);
+        assert_eq!(
+            recorder.sides[0].1,
+            lines(&[(4, "date"), (3, "kiwi"), (0, "pear"), (5, "plum")])
+        );
+        assert_eq!((recorder.sides[0].2, recorder.sides[0].3), (4, false));
+        assert_eq!(recorder.sides[1].0, Side::SecondOnly);
+        assert_eq!(recorder.sides[1].1, lines(&[(3, "cherry"), (1, "lime")]));
+    }
+
+    #[test]
+    fn test_sorted_by_line() {
+        let recorder = record(StreamOrder::ByLine(TINY_BUDGET), None);
+        assert_eq!(
+            recorder.sides[0].1,
+            lines(&[(0, "pear"), (3, "kiwi"), (4, "date"), (5, "plum")])
+        );
+        assert_eq!(recorder.sides[1].1, lines(&[(1, "lime"), (3, "cherry")]));
+    }
+
+    #[test]
+    fn test_limit() {
+        let recorder = record(StreamOrder::Unsorted, Some(2));
+        assert_eq!(recorder.sides[0].1, lines(&[(4, "date"), (3, "kiwi")]));
+        assert_eq!((recorder.sides[0].2, recorder.sides[0].3), (2, true));
+        // Exactly at the limit is not truncated
+        assert_eq!((recorder.sides[1].2, recorder.sides[1].3), (2, false));
+
+        let recorder = record(StreamOrder::ByLine(0), Some(3));
+        assert_eq!(
+            recorder.sides[0].1,
+            lines(&[(0, "pear"), (3, "kiwi"), (4, "date")])
+        );
+        assert_eq!((recorder.sides[0].2, recorder.sides[0].3), (3, true));
+        assert_eq!(recorder.sides[1].1, lines(&[(1, "lime"), (3, "cherry")]));
+        assert!(!recorder.sides[1].3);
+    }
+
+    #[test]
+    fn test_sorted_stream_matches_batch_report() {
+        let (first, second) = tables();
+        let mut only_in_first = keys_missing_from(&first, &second);
+        let mut only_in_second = keys_missing_from(&second, &first);
+        only_in_first.sort_by_key(|k| k.0);
+        only_in_second.sort_by_key(|k| k.0);
+        let result = ComparisonResult {
+            first: "a.txt".to_string(),
+            second: "b.txt".to_string(),
+            only_in_first,
+            only_in_second,
+            moved: None,
+            changed: None,
+            first_distinct: first.get_size(),
+            second_distinct: second.get_size(),
+            first_total: 6,
+            second_total: 4,
+        };
+        let summary = summarize(&first, 6, &second, 4);
+        assert_eq!(result.summary(), summary);
+
+        let mut batch = Vec::new();
+        TextRenderer.render(&result, &mut batch).unwrap();
+        let mut streamed = TextStream::new(Vec::new(), "a.txt", "b.txt").with_summary(summary);
+        let order = StreamOrder::ByLine(TINY_BUDGET);
+        stream_differences(&first, &second, order, None, &mut streamed).unwrap();
+        assert_eq!(*streamed.writer(), batch);
+
+        let mut batch = Vec::new();
+        HtmlRenderer.render(&result, &mut batch).unwrap();
+        let mut streamed = HtmlStream::new(Vec::new(), "a.txt", "b.txt").with_summary(summary);
+        stream_differences(&first, &second, order, None, &mut streamed).unwrap();
+        assert_eq!(*streamed.writer(), batch);
+    }
+}
@@ -0,0 +1,90 @@
+//! Counts and similarity metrics of a comparison, printed at the head of
+//! every report and alone with `--summary-only`.
+//!
+//! Lines are counted after normalization and without empty lines, as they
+//! are stored: "total" counts every occurrence, "distinct" each line once.
+
+use tries::SymbolTable;
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+/// Line counts of both files and the overlap of their distinct lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Summary {
+    pub first_total: usize,
+    pub first_distinct: usize,
+    pub second_total: usize,
+    pub second_distinct: usize,
+    /// Distinct lines found in both files
+    pub common: usize,
+}
+
+impl Summary {
+    /// Occurrences of lines in the first file beyond their first.
+    pub fn first_duplicates(&self) -> usize {
+        self.first_total - self.first_distinct
+    }
+
+    /// Occurrences of lines in the second file beyond their first.
+    pub fn second_duplicates(&self) -> usize {
+        self.second_total - self.second_distinct
+    }
+
+    /// Common distinct lines over the distinct lines of either file; two
+    /// empty files are identical.
+    pub fn jaccard(&self) -> f64 {
+        ratio(
+            self.common,
+            self.first_distinct + self.second_distinct - self.common,
+        )
+    }
+
+    /// Share of the first file's distinct lines also in the second.
+    pub fn first_in_second(&self) -> f64 {
+        ratio(self.common, self.first_distinct)
+    }
+
+    /// Share of the second file's distinct lines also in the first.
+    pub fn second_in_first(&self) -> f64 {
+        ratio(self.common, self.second_distinct)
+    }
+}
+
+// An empty whole is taken as fully covered
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        1.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// Number of keys of `symbol_table_1` also in `symbol_table_2`.
+pub fn common_lines<T: SymbolTable<u32>>(symbol_table_1: &T, symbol_table_2: &T) -> usize {
+    let mut common = 0;
+    symbol_table_1.for_each_entry(&mut |key, _| {
+        if symbol_table_2.contains(key) {
+            common += 1;
+        }
+        true
+    });
+    common
+}
+
+/// The summary of two tables read from files of `first_total` and
+/// `second_total` non-empty lines.
+pub fn summarize<T: SymbolTable<u32>>(
+    symbol_table_1: &T,
+    first_total: usize,
+    symbol_table_2: &T,
+    second_total: usize,
+) -> Summary {
+    Summary {
+        first_total,
+        first_distinct: symbol_table_1.get_size(),
+        second_total,
+        second_distinct: symbol_table_2.get_size(),
+        common: common_lines(symbol_table_1, symbol_table_2),
+    }
+}
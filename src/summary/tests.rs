@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use crate::summary::{Summary, summarize};
+    use tries::{SymbolTable, TernarySearchTrie};
+
+    fn table(lines: &[&str]) -> TernarySearchTrie<u32> {
+        let mut table = TernarySearchTrie::new();
+        for (index, line) in lines.iter().enumerate() {
+            table.put(line.to_string(), index as u32);
+        }
+        table
+    }
+
+    #[test]
+    fn test_summarize() {
+        let first = table(&["a", "b", "c", "d", "a"]);
+        let second = table(&["c", "d", "e"]);
+        let summary = summarize(&first, 5, &second, 3);
+        assert_eq!(
+            summary,
+            Summary {
+                first_total: 5,
+                first_distinct: 4,
+                second_total: 3,
+                second_distinct: 3,
+                common: 2,
+            }
+        );
+        assert_eq!((summary.first_duplicates(), summary.second_duplicates()), (1, 0));
+        assert_eq!(summary.jaccard(), 2.0 / 5.0);
+        assert_eq!(summary.first_in_second(), 0.5);
+        assert_eq!(summary.second_in_first(), 2.0 / 3.0);
+    }
+
+    #[test]
+    fn test_empty_files() {
+        let empty = table(&[]);
+        let summary = summarize(&empty, 0, &empty, 0);
+        assert_eq!(summary.jaccard(), 1.0);
+        assert_eq!(summary.first_in_second(), 1.0);
+
+        let other = table(&["x"]);
+        let summary = summarize(&empty, 0, &other, 1);
+        assert_eq!(summary.jaccard(), 0.0);
+        assert_eq!(summary.first_in_second(), 1.0);
+        assert_eq!(summary.second_in_first(), 0.0);
+    }
+}
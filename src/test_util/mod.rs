@@ -0,0 +1,58 @@
+//! Temporary files for the unit tests.
+//!
+//! Each path is unique to the test process and to the call that made it, so
+//! tests running at the same time never share a file, and whatever was
+//! created at it is removed when the [`TempPath`] is dropped, even when a
+//! test fails.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A path in the temporary folder whose file, or folder, is removed on drop.
+pub struct TempPath {
+    path: PathBuf,
+}
+
+impl TempPath {
+    /// The path as a string, the way inputs are named to the code under test.
+    pub fn as_str(&self) -> &str {
+        self.path.to_str().expect("temporary paths are UTF-8")
+    }
+}
+
+impl AsRef<Path> for TempPath {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = if self.path.is_dir() {
+            fs::remove_dir_all(&self.path)
+        } else {
+            fs::remove_file(&self.path)
+        };
+    }
+}
+
+/// A path ending in `name`, with nothing created at it yet.
+pub fn temp_path(name: &str) -> TempPath {
+    static NEXT_PATH: AtomicUsize = AtomicUsize::new(0);
+    TempPath {
+        path: std::env::temp_dir().join(format!(
+            "file_compare-{}-{}-{}",
+            std::process::id(),
+            NEXT_PATH.fetch_add(1, Ordering::Relaxed),
+            name
+        )),
+    }
+}
+
+/// A file ending in `name` holding `contents`.
+pub fn write_temp(name: &str, contents: impl AsRef<[u8]>) -> TempPath {
+    let path = temp_path(name);
+    fs::write(&path, contents).unwrap();
+    path
+}
@@ -0,0 +1,235 @@
+//! Three-way comparison against a common base, for `--base`.
+//!
+//! Every distinct line of the three files is keyed once in a trie whose value
+//! records, per file, where the line first occurs and how often. Each line is
+//! then classified by which files contain it:
+//!
+//! | base | first | second | class                                   |
+//! |------|-------|--------|-----------------------------------------|
+//! | no   | yes   | no     | added in first                          |
+//! | no   | no    | yes    | added in second                         |
+//! | yes  | no    | yes    | removed in first, or conflicting        |
+//! | yes  | yes   | no     | removed in second, or conflicting       |
+//!
+//! A removal conflicts when the other side did not leave the line alone but
+//! changed how many times it occurs. Lines present everywhere, added on both
+//! sides, or removed on both sides need no reconciliation and are not reported.
+
+use crate::patch::read_all_lines;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use tries::{SymbolTable, TernarySearchTrie};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+const BASE: usize = 0;
+const FIRST: usize = 1;
+const SECOND: usize = 2;
+
+/// Where a line first occurs in the base, first and second file, and how many
+/// times it occurs in each.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Membership {
+    pub lines: [Option<u32>; 3],
+    pub counts: [u32; 3],
+}
+
+impl Membership {
+    fn contains(&self, file: usize) -> bool {
+        self.counts[file] > 0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Class {
+    AddedInFirst,
+    AddedInSecond,
+    RemovedInFirst,
+    RemovedInSecond,
+    Conflicting,
+}
+
+impl Class {
+    pub fn classify(membership: &Membership) -> Option<Class> {
+        let in_base = membership.contains(BASE);
+        let in_first = membership.contains(FIRST);
+        let in_second = membership.contains(SECOND);
+        match (in_base, in_first, in_second) {
+            (false, true, false) => Some(Class::AddedInFirst),
+            (false, false, true) => Some(Class::AddedInSecond),
+            (true, false, true) if membership.counts[SECOND] == membership.counts[BASE] => {
+                Some(Class::RemovedInFirst)
+            }
+            (true, true, false) if membership.counts[FIRST] == membership.counts[BASE] => {
+                Some(Class::RemovedInSecond)
+            }
+            (true, false, true) | (true, true, false) => Some(Class::Conflicting),
+            _ => None,
+        }
+    }
+}
+
+/// A classified line, with its membership for reporting line numbers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThreeWayLine {
+    pub text: String,
+    pub membership: Membership,
+}
+
+impl ThreeWayLine {
+    /// The line number to report: in the file that added the line, or in the
+    /// base for removals and conflicts.
+    pub fn line(&self, class: Class) -> u32 {
+        let file = match class {
+            Class::AddedInFirst => FIRST,
+            Class::AddedInSecond => SECOND,
+            _ => BASE,
+        };
+        self.membership.lines[file].unwrap()
+    }
+
+    /// For a conflict, whether it is the first file that removed the line.
+    pub fn removed_in_first(&self) -> bool {
+        !self.membership.contains(FIRST)
+    }
+}
+
+/// The report of a three-way comparison, each list in line-number order.
+#[derive(Debug, Default)]
+pub struct ThreeWayReport {
+    pub added_in_first: Vec<ThreeWayLine>,
+    pub added_in_second: Vec<ThreeWayLine>,
+    pub removed_in_first: Vec<ThreeWayLine>,
+    pub removed_in_second: Vec<ThreeWayLine>,
+    pub conflicting: Vec<ThreeWayLine>,
+}
+
+pub struct ThreeWayComparison {
+    symbol_table: TernarySearchTrie<Membership>,
+    ignore_case: bool,
+}
+
+impl ThreeWayComparison {
+    pub fn new(base: &[String], first: &[String], second: &[String], ignore_case: bool) -> Self {
+        let mut comparison = ThreeWayComparison {
+            symbol_table: TernarySearchTrie::new(),
+            ignore_case,
+        };
+        for (file, lines) in [base, first, second].into_iter().enumerate() {
+            for (index, line) in lines.iter().enumerate() {
+                comparison.add(file, index as u32, line);
+            }
+        }
+        comparison
+    }
+
+    fn key(&self, line: &str) -> String {
+        if self.ignore_case {
+            line.to_uppercase()
+        } else {
+            line.to_string()
+        }
+    }
+
+    fn add(&mut self, file: usize, index: u32, line: &str) {
+        let key = self.key(line);
+        if key.is_empty() {
+            return;
+        }
+        let mut membership = self.symbol_table.get(&key).unwrap_or_default();
+        membership.lines[file].get_or_insert(index);
+        membership.counts[file] += 1;
+        self.symbol_table.put(key, membership);
+    }
+
+    fn class_of(&self, line: &str) -> Option<Class> {
+        self.symbol_table
+            .get(&self.key(line))
+            .and_then(|membership| Class::classify(&membership))
+    }
+
+    pub fn report(&self) -> ThreeWayReport {
+        let mut report = ThreeWayReport::default();
+        for key in self.symbol_table.get_all_keys() {
+            let membership = self.symbol_table.get(&key).unwrap();
+            let Some(class) = Class::classify(&membership) else {
+                continue;
+            };
+            let list = match class {
+                Class::AddedInFirst => &mut report.added_in_first,
+                Class::AddedInSecond => &mut report.added_in_second,
+                Class::RemovedInFirst => &mut report.removed_in_first,
+                Class::RemovedInSecond => &mut report.removed_in_second,
+                Class::Conflicting => &mut report.conflicting,
+            };
+            list.push(ThreeWayLine {
+                text: key,
+                membership,
+            });
+        }
+        for (class, list) in [
+            (Class::AddedInFirst, &mut report.added_in_first),
+            (Class::AddedInSecond, &mut report.added_in_second),
+            (Class::RemovedInFirst, &mut report.removed_in_first),
+            (Class::RemovedInSecond, &mut report.removed_in_second),
+            (Class::Conflicting, &mut report.conflicting),
+        ] {
+            list.sort_by_key(|line| line.line(class));
+        }
+        report
+    }
+
+    /// Merges the two sides: the first file's lines in order, minus those the
+    /// second removed, followed by the lines the second added. Conflicting
+    /// lines are kept from whichever side still has them, to be resolved by
+    /// hand.
+    pub fn merge(&self, first: &[String], second: &[String]) -> Vec<String> {
+        let mut merged: Vec<String> = first
+            .iter()
+            .filter(|line| self.class_of(line) != Some(Class::RemovedInSecond))
+            .cloned()
+            .collect();
+        merged.extend(
+            second
+                .iter()
+                .filter(|line| match self.class_of(line) {
+                    Some(Class::AddedInSecond) => true,
+                    // Only the conflicts the first file no longer has
+                    Some(Class::Conflicting) => {
+                        self.symbol_table.get(&self.key(line)).unwrap().counts[FIRST] == 0
+                    }
+                    _ => false,
+                })
+                .cloned(),
+        );
+        merged
+    }
+}
+
+/// Reads the three files, returning the comparison and the two sides' lines
+/// for merging.
+pub fn compare_three_way(
+    base: &str,
+    first: &str,
+    second: &str,
+    ignore_case: bool,
+) -> io::Result<(ThreeWayComparison, Vec<String>, Vec<String>)> {
+    let read = |filename: &str| {
+        read_all_lines(filename)
+            .map_err(|error| io::Error::new(error.kind(), format!("{}: {}", filename, error)))
+    };
+    let base_lines = read(base)?;
+    let first_lines = read(first)?;
+    let second_lines = read(second)?;
+    let comparison = ThreeWayComparison::new(&base_lines, &first_lines, &second_lines, ignore_case);
+    Ok((comparison, first_lines, second_lines))
+}
+
+pub fn write_merged(filename: &str, merged: &[String]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(filename)?);
+    for line in merged {
+        writeln!(writer, "{}", line)?;
+    }
+    writer.flush()
+}
@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tests {
+    use crate::three_way::{Class, ThreeWayComparison};
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    fn texts(lines: &[crate::three_way::ThreeWayLine]) -> Vec<&str> {
+        lines.iter().map(|line| line.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_classification() {
+        let base = lines(&["she", "sells", "sea", "shells", "by"]);
+        let first = lines(&["she", "sea", "shells", "by", "the", "both"]);
+        let second = lines(&["she", "sells", "sea", "by", "shore", "both", "by"]);
+
+        let comparison = ThreeWayComparison::new(&base, &first, &second, false);
+        let report = comparison.report();
+
+        assert_eq!(texts(&report.added_in_first), ["the"]);
+        assert_eq!(texts(&report.added_in_second), ["shore"]);
+        assert_eq!(texts(&report.removed_in_first), ["sells"]);
+        assert_eq!(texts(&report.removed_in_second), ["shells"]);
+        assert!(report.conflicting.is_empty());
+
+        assert_eq!(report.added_in_first[0].line(Class::AddedInFirst), 4);
+        assert_eq!(report.removed_in_first[0].line(Class::RemovedInFirst), 1);
+    }
+
+    #[test]
+    fn test_conflict_when_other_side_changed_the_line() {
+        let base = lines(&["she", "sea"]);
+        let first = lines(&["she"]);
+        let second = lines(&["she", "sea", "sea"]);
+
+        let comparison = ThreeWayComparison::new(&base, &first, &second, false);
+        let report = comparison.report();
+
+        assert_eq!(texts(&report.conflicting), ["sea"]);
+        assert!(report.conflicting[0].removed_in_first());
+        assert!(report.removed_in_first.is_empty());
+
+        // The conflict survives the merge for a person to resolve
+        assert_eq!(
+            comparison.merge(&first, &second),
+            lines(&["she", "sea", "sea"])
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let base = lines(&["She", "sells", "sea"]);
+        let first = lines(&["she", "sells", "shells"]);
+        let second = lines(&["SHE", "sea", "shore"]);
+
+        let comparison = ThreeWayComparison::new(&base, &first, &second, true);
+
+        assert_eq!(
+            comparison.merge(&first, &second),
+            lines(&["she", "shells", "shore"])
+        );
+    }
+}
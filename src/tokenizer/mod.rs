@@ -0,0 +1,126 @@
+//! Splitting lines into the units compared by `--unit word|token|char-ngram`.
+//!
+//! Each unit is stored in the symbol table together with an [`Occurrence`]:
+//! where it was first seen and how many times it appears in the file.
+
+use crate::clap_parser::Unit;
+use crate::line_source::LineSource;
+use regex::Regex;
+use std::io;
+use tries::{SymbolTable, TernarySearchTrie};
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+/// Where a unit was first seen, and how often it occurs in its file.
+///
+/// `line` and `column` are zero-based, like the line numbers of the line
+/// report; `column` counts characters, not bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Occurrence {
+    pub line: u32,
+    pub column: u32,
+    pub count: u32,
+}
+
+pub enum Tokenizer {
+    Separated(Regex),
+    CharNgram(usize),
+}
+
+impl Tokenizer {
+    /// Builds the tokenizer for `unit`, or `None` for `Unit::Line`, which is
+    /// compared without splitting.
+    pub fn new(unit: Unit, separator: Option<Regex>, ngram_size: usize) -> Option<Self> {
+        let default_separator = match unit {
+            Unit::Line => return None,
+            Unit::CharNgram => return Some(Tokenizer::CharNgram(ngram_size)),
+            Unit::Word => r"\W+",
+            Unit::Token => r"\s+",
+        };
+        let separator =
+            separator.unwrap_or_else(|| Regex::new(default_separator).expect("valid regex"));
+        Some(Tokenizer::Separated(separator))
+    }
+
+    /// Returns the units of `line` with the character column each starts at.
+    /// Empty pieces between adjacent separators are skipped.
+    pub fn tokenize<'a>(&self, line: &'a str) -> Vec<(usize, &'a str)> {
+        match self {
+            Tokenizer::Separated(separator) => {
+                let mut tokens = Vec::new();
+                let mut start = 0;
+                let pieces = separator
+                    .find_iter(line)
+                    .map(|m| (m.start(), m.end()))
+                    .chain(std::iter::once((line.len(), line.len())));
+                for (end, next_start) in pieces {
+                    if end > start {
+                        tokens.push((line[..start].chars().count(), &line[start..end]));
+                    }
+                    start = next_start;
+                }
+                tokens
+            }
+            Tokenizer::CharNgram(size) => {
+                let bounds: Vec<usize> = line
+                    .char_indices()
+                    .map(|(i, _)| i)
+                    .chain(std::iter::once(line.len()))
+                    .collect();
+                let chars = bounds.len() - 1;
+                if chars == 0 {
+                    return Vec::new();
+                }
+                // A line shorter than one n-gram still contributes itself
+                if chars <= *size {
+                    return vec![(0, line)];
+                }
+                (0..=chars - size)
+                    .map(|column| (column, &line[bounds[column]..bounds[column + size]]))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Tokenizes every line of `filename`, which may be `-` for standard input,
+/// into `symbol_table`, keeping the first position of each unit and counting
+/// repeats.
+pub fn build_token_table(
+    filename: &str,
+    tokenizer: &Tokenizer,
+    symbol_table: &mut TernarySearchTrie<Occurrence>,
+    ignore_case: bool,
+) -> io::Result<()> {
+    LineSource::open(filename)?.for_each_line(|index, current_line| {
+        let current_line = if ignore_case {
+            current_line.to_uppercase()
+        } else {
+            current_line.to_string()
+        };
+        for (column, token) in tokenizer.tokenize(&current_line) {
+            add_occurrence(symbol_table, token, index as u32, column as u32);
+        }
+    })
+}
+
+fn add_occurrence(
+    symbol_table: &mut TernarySearchTrie<Occurrence>,
+    token: &str,
+    line: u32,
+    column: u32,
+) {
+    let occurrence = match symbol_table.get(token) {
+        Some(seen) => Occurrence {
+            count: seen.count + 1,
+            ..seen
+        },
+        None => Occurrence {
+            line,
+            column,
+            count: 1,
+        },
+    };
+    symbol_table.put(token.to_string(), occurrence);
+}
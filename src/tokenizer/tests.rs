@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod tests {
+    use crate::clap_parser::Unit;
+    use crate::tokenizer::{Occurrence, Tokenizer, add_occurrence};
+    use regex::Regex;
+    use tries::{SymbolTable, TernarySearchTrie};
+
+    fn tokenize(unit: Unit, line: &str) -> Vec<(usize, String)> {
+        let tokenizer = Tokenizer::new(unit, None, 3).unwrap();
+        tokenizer
+            .tokenize(line)
+            .into_iter()
+            .map(|(column, token)| (column, token.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_line_unit_has_no_tokenizer() {
+        assert!(Tokenizer::new(Unit::Line, None, 3).is_none());
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(
+            tokenize(Unit::Word, "user_123, active; (admin)"),
+            [
+                (0, "user_123".to_string()),
+                (10, "active".to_string()),
+                (19, "admin".to_string())
+            ]
+        );
+        assert!(tokenize(Unit::Word, "  ,;  ").is_empty());
+    }
+
+    #[test]
+    fn test_tokens() {
+        assert_eq!(
+            tokenize(Unit::Token, "  a,b   c "),
+            [(2, "a,b".to_string()), (8, "c".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_custom_separator() {
+        let tokenizer = Tokenizer::new(Unit::Token, Some(Regex::new(",").unwrap()), 3).unwrap();
+        assert_eq!(tokenizer.tokenize("a b,,c"), [(0, "a b"), (5, "c")]);
+    }
+
+    #[test]
+    fn test_columns_count_characters() {
+        assert_eq!(
+            tokenize(Unit::Token, "héllo wörld"),
+            [(0, "héllo".to_string()), (6, "wörld".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_char_ngrams() {
+        assert_eq!(
+            tokenize(Unit::CharNgram, "shore"),
+            [
+                (0, "sho".to_string()),
+                (1, "hor".to_string()),
+                (2, "ore".to_string())
+            ]
+        );
+        assert_eq!(tokenize(Unit::CharNgram, "sé"), [(0, "sé".to_string())]);
+        assert!(tokenize(Unit::CharNgram, "").is_empty());
+    }
+
+    #[test]
+    fn test_occurrence_keeps_first_position() {
+        let mut table = TernarySearchTrie::<Occurrence>::new();
+        add_occurrence(&mut table, "sea", 2, 4);
+        add_occurrence(&mut table, "sea", 5, 0);
+        add_occurrence(&mut table, "she", 6, 1);
+
+        assert_eq!(
+            table.get("sea"),
+            Some(Occurrence {
+                line: 2,
+                column: 4,
+                count: 2
+            })
+        );
+        assert_eq!(table.get("she").unwrap().count, 1);
+    }
+}
@@ -0,0 +1,144 @@
+//! `--watch`: re-running the comparison whenever either file changes.
+//!
+//! The directories holding the files are watched rather than the files
+//! themselves, since editors and export jobs often save by writing a new file
+//! and renaming it over the old one, which would orphan a watch on the old
+//! file. A burst of events is waited out until the files have been quiet for
+//! a moment, so a half-written file is not compared.
+
+use std::collections::HashSet;
+
+#[allow(clippy::module_inception)]
+mod tests;
+
+/// How long the files must be left alone before they are compared again.
+pub const DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// How one list of differences changed between two runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Delta {
+    /// Lines listed now but not on the previous run
+    pub added: usize,
+    /// Lines listed on the previous run but not now
+    pub removed: usize,
+}
+
+/// Compares the texts of two lists of differences.
+pub fn delta(previous: &[(u32, String)], current: &[(u32, String)]) -> Delta {
+    let previous_texts: HashSet<&str> = previous.iter().map(|(_, text)| text.as_str()).collect();
+    let current_texts: HashSet<&str> = current.iter().map(|(_, text)| text.as_str()).collect();
+    Delta {
+        added: current_texts.difference(&previous_texts).count(),
+        removed: previous_texts.difference(&current_texts).count(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use inotify_watcher::Watcher;
+
+/// Stands in for the inotify watcher elsewhere, failing to start.
+#[cfg(not(target_os = "linux"))]
+pub struct Watcher;
+
+#[cfg(not(target_os = "linux"))]
+impl Watcher {
+    pub fn new(_paths: &[&str]) -> std::io::Result<Self> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "watching relies on inotify, which only Linux has",
+        ))
+    }
+
+    pub fn wait(&mut self, _debounce: std::time::Duration) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod inotify_watcher {
+    use inotify::{Inotify, WatchDescriptor, WatchMask};
+    use std::ffi::OsString;
+    use std::io;
+    use std::path::Path;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    // Polling step while waiting for a burst of events to end
+    const STEP: Duration = Duration::from_millis(50);
+
+    /// Waits for changes to a set of files.
+    pub struct Watcher {
+        inotify: Inotify,
+        // The watch of each file's directory, with the file's name in it
+        files: Vec<(WatchDescriptor, OsString)>,
+        buffer: Vec<u8>,
+    }
+
+    impl Watcher {
+        pub fn new(paths: &[&str]) -> io::Result<Self> {
+            let inotify = Inotify::init()?;
+            let mut files = Vec::new();
+            for path in paths {
+                let path = Path::new(path);
+                let name = path.file_name().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "not a file path")
+                })?;
+                let directory = match path.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent,
+                    _ => Path::new("."),
+                };
+                // Adding a directory twice gives back the same descriptor
+                let watch = inotify.watches().add(
+                    directory,
+                    WatchMask::MODIFY
+                        | WatchMask::CLOSE_WRITE
+                        | WatchMask::CREATE
+                        | WatchMask::MOVED_TO
+                        | WatchMask::DELETE,
+                )?;
+                files.push((watch, name.to_os_string()));
+            }
+            Ok(Watcher {
+                inotify,
+                files,
+                buffer: vec![0; 4096],
+            })
+        }
+
+        // Whether any pending event concerns a watched file, without
+        // blocking unless `block` is set
+        fn changed(&mut self, block: bool) -> io::Result<bool> {
+            let events = if block {
+                self.inotify.read_events_blocking(&mut self.buffer)
+            } else {
+                self.inotify.read_events(&mut self.buffer)
+            };
+            let events = match events {
+                Ok(events) => events,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Ok(false),
+                Err(error) => return Err(error),
+            };
+            let mut changed = false;
+            for event in events {
+                changed |= self.files.iter().any(|(watch, name)| {
+                    event.wd == *watch && event.name.is_some_and(|event_name| event_name == name)
+                });
+            }
+            Ok(changed)
+        }
+
+        /// Blocks until a watched file changes and then stays unchanged for
+        /// `debounce`.
+        pub fn wait(&mut self, debounce: Duration) -> io::Result<()> {
+            while !self.changed(true)? {}
+            let mut quiet_since = Instant::now();
+            while quiet_since.elapsed() < debounce {
+                thread::sleep(STEP.min(debounce));
+                if self.changed(false)? {
+                    quiet_since = Instant::now();
+                }
+            }
+            Ok(())
+        }
+    }
+}
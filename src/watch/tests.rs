@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use crate::test_util::temp_path;
+    use crate::watch::{Delta, delta};
+
+    fn lines(texts: &[&str]) -> Vec<(u32, String)> {
+        texts
+            .iter()
+            .enumerate()
+            .map(|(num, text)| (num as u32, text.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_delta() {
+        let previous = lines(&["a", "b", "c"]);
+        let current = lines(&["c", "d", "e", "b"]);
+        assert_eq!(
+            delta(&previous, &current),
+            Delta {
+                added: 2,
+                removed: 1
+            }
+        );
+        assert_eq!(delta(&current, &current), Delta::default());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_watcher_sees_replaced_file() {
+        use crate::watch::Watcher;
+        use std::time::{Duration, Instant};
+
+        let dir = temp_path("watch");
+        std::fs::create_dir_all(&dir).unwrap();
+        let watched = dir.as_ref().join("watched.txt");
+        let other = dir.as_ref().join("other.txt");
+        std::fs::write(&watched, "old\n").unwrap();
+
+        let mut watcher = Watcher::new(&[watched.to_str().unwrap()]).unwrap();
+        let writer = {
+            let (watched, other) = (watched.clone(), other.clone());
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                // Unrelated files in the directory are ignored
+                std::fs::write(&other, "noise\n").unwrap();
+                let temp = watched.with_extension("tmp");
+                std::fs::write(&temp, "new\n").unwrap();
+                std::fs::rename(&temp, &watched).unwrap();
+            })
+        };
+        let started = Instant::now();
+        watcher.wait(Duration::from_millis(100)).unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        writer.join().unwrap();
+        assert_eq!(std::fs::read_to_string(&watched).unwrap(), "new\n");
+    }
+}
@@ -0,0 +1,45 @@
+//! Reports piped into a reader that stops early, as `| head` does. The tool
+//! must end quietly and successfully rather than panic on the closed pipe.
+
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+// A file of `lines` distinct lines, each starting with `prefix`
+fn write_lines(name: &str, prefix: &str, lines: usize) -> String {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let text: String = (0..lines)
+        .map(|line| format!("{}.{} token{}\n", prefix, line, line))
+        .collect();
+    std::fs::write(&path, text).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_reader_stops_early() {
+    // Far more output than a pipe buffers
+    let first = write_lines("broken-pipe-first.txt", "first", 20_000);
+    let second = write_lines("broken-pipe-second.txt", "second", 20_000);
+    let modes: [&[&str]; 5] = [
+        &[],
+        &["--unit", "token"],
+        &["--unit", "token", "--render-html"],
+        &["--group-by-prefix"],
+        &["--emit-patch", "ed"],
+    ];
+    for args in modes {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_file_compare"))
+            .args(["-f", &first, "-s", &second])
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut head = [0; 64];
+        child.stdout.take().unwrap().read_exact(&mut head).unwrap();
+        let output = child.wait_with_output().unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "{:?}: {}", args, stderr);
+        assert!(stderr.is_empty(), "{:?}: {}", args, stderr);
+    }
+}
@@ -0,0 +1,39 @@
+//! The `history` reports name each version by its place in the series as
+//! well as its file, so a file given twice is told apart.
+
+use std::process::Command;
+
+const FIRST: &str = "src/test_data/first.txt";
+const SECOND: &str = "src/test_data/second.txt";
+
+fn history(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_file_compare"))
+        .args([&["history", FIRST, SECOND, FIRST], args].concat())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", args);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_repeated_file_versions() {
+    // "missing1" is only in the first file, "shore" only in the second
+    let json = history(&["--json"]);
+    let first = |version| format!("{{\"version\": {}, \"file\": \"{}\"}}", version, FIRST);
+    assert!(json.contains(&format!(
+        "{{\"text\": \"missing1\", \"appeared\": {}, \"disappeared\": null",
+        first(0)
+    )));
+    assert!(json.contains(&format!("{{\"from\": {}, \"to\": {}}}", first(2), first(2))));
+    assert!(json.contains(&format!("\"disappeared\": {}", first(2))));
+
+    let text = history(&[]);
+    assert!(text.contains(&format!(
+        "appeared {} (file 0), flapped (back in {} (file 2)): missing1",
+        FIRST, FIRST
+    )));
+    assert!(text.contains(&format!(
+        "appeared {} (file 1), disappeared {} (file 2): shore",
+        SECOND, FIRST
+    )));
+}
@@ -0,0 +1,75 @@
+//! Every way of reading the two files, given one of them as `-` for standard
+//! input. These run the built tool, as only a separate process can be handed
+//! its own standard input.
+
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Output, Stdio};
+
+const FIRST: &str = "src/test_data/first.txt";
+const SECOND: &str = "src/test_data/second.txt";
+
+// Runs the tool with `args`, feeding it `input` on standard input
+fn run(args: &[&str], input: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_file_compare"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // A run that fails early exits without reading its input
+    match child.stdin.take().unwrap().write_all(input) {
+        Err(error) if error.kind() != ErrorKind::BrokenPipe => panic!("{}", error),
+        _ => {}
+    }
+    child.wait_with_output().unwrap()
+}
+
+// The report with `file` read from standard input, its name put back in
+// place of `-`, and the report with `file` read by name
+fn reports(args: &[&str], flag: &str, file: &str) -> (String, String) {
+    let (first, second) = if flag == "-f" {
+        ("-", SECOND)
+    } else {
+        (FIRST, "-")
+    };
+    let piped = run(
+        &[&["-f", first, "-s", second], args].concat(),
+        &std::fs::read(file).unwrap(),
+    );
+    assert!(piped.status.success(), "{:?} {}", args, flag);
+    let named = run(&[&["-f", FIRST, "-s", SECOND], args].concat(), b"");
+    assert!(named.status.success(), "{:?}", args);
+    (
+        String::from_utf8(piped.stdout)
+            .unwrap()
+            .replace("(-)", &format!("({})", file)),
+        String::from_utf8(named.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn test_strategies_read_standard_input() {
+    let modes: [&[&str]; 6] = [
+        &[],
+        &["--threads", "4"],
+        &["--memory-limit", "1M"],
+        &["--unit", "word"],
+        &["--unit", "token"],
+        &["--unit", "char-ngram"],
+    ];
+    for args in modes {
+        for (flag, file) in [("-f", FIRST), ("-s", SECOND)] {
+            let (piped, named) = reports(args, flag, file);
+            assert_eq!(piped, named, "{:?} with {} -", args, flag);
+        }
+    }
+}
+
+#[test]
+fn test_hash_rejects_standard_input() {
+    // Lines are re-read by offset, which standard input cannot do
+    let output = run(&["-f", "-", "-s", SECOND, "--hash"], b"alpha\n");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("regular file"));
+}